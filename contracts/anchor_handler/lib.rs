@@ -7,35 +7,45 @@ mod anchor_handler {
     use ink_env::call::ExecutionInput;
     use ink_env::call::{build_call, Call, Selector};
     use ink_env::DefaultEnvironment;
+    // the dependency contract implements `EmitEvent` too, so events name this contract explicitly
+    use ink_lang::codegen::EmitEvent;
+    use ink_lang::ToAccountId;
     use ink_prelude::string::String;
     use ink_prelude::vec::Vec;
     use ink_storage::traits::{PackedLayout, SpreadLayout, StorageLayout};
     use ink_storage::{traits::SpreadAllocate, Mapping};
-    use protocol_ink_lib::blake::blake2b_256_4_bytes_output;
+    use protocol_ink_lib::blake::{blake2b_256_32_bytes_output, blake2b_256_4_bytes_output};
     use protocol_ink_lib::keccak::Keccak256;
-    use protocol_ink_lib::utils::{
-        element_encoder, element_encoder_for_eight_bytes, element_encoder_for_four_bytes,
-        element_encoder_for_one_byte, element_encoder_for_sixteen_bytes,
+    use protocol_ink_lib::proposals::{
+        decode_proposal, AnchorLimitProposal, AnchorSetHandlerProposal, AnchorUpdateProposal,
+        ProposalBody, ProposalHeader,
     };
+    use protocol_ink_lib::utils::{typed_chain_id, ZERO_ADDRESS};
     use vanchor::vanchor::TokenWrapperData;
     use vanchor::VAnchorRef;
 
     /// The anchor handler result type.
     pub type Result<T> = core::result::Result<T, Error>;
+    /// The maximum number of update records returned by one query
+    pub const MAX_UPDATE_RECORDS_PER_PAGE: u32 = 100;
 
     #[ink(storage)]
     #[derive(SpreadAllocate)]
     pub struct AnchorHandler {
         /// Contract address of previously deployed Bridge.
         bridge_address: AccountId,
+        /// Account allowed to set resources while bootstrapping, zero once renounced
+        admin: AccountId,
         /// resourceID => token contract address
         resource_id_to_contract_address: Mapping<[u8; 32], AccountId>,
         /// Execution contract address => resourceID
         contract_address_to_resource_id: Mapping<AccountId, [u8; 32]>,
         /// Execution contract address => is whitelisted
         contract_whitelist: Mapping<AccountId, bool>,
-        /// (src_chain_id, height) -> UpdateRecord
+        /// (src_chain_id, sequence) -> UpdateRecord, the sequence counts the records of the chain
         update_records: Mapping<(u64, u64), UpdateRecord>,
+        /// chain_id -> number of update records
+        update_record_counts: Mapping<u64, u64>,
         vanchor: VAnchorRef,
     }
 
@@ -43,9 +53,16 @@ mod anchor_handler {
     #[cfg_attr(feature = "std", derive(StorageLayout, scale_info::TypeInfo))]
     pub struct UpdateRecord {
         pub token_address: AccountId,
+        /// the source chain of an edge update, the anchor chain for other proposals
         pub src_chain_id: u64,
         pub resource_id: [u8; 32],
+        /// the executed function signature
+        pub function_signature: [u8; 4],
+        /// the blake2b-256 hash of the proposal data
+        pub proposal_hash: [u8; 32],
+        /// the edge root, zero for other proposals
         pub merkle_root: [u8; 32],
+        /// the edge leaf index, zero for other proposals
         pub leaf_id: u32,
     }
 
@@ -55,17 +72,36 @@ mod anchor_handler {
         pub max_edges: u32,
         pub chain_id: u64,
         pub levels: u32,
+        pub root_history_size: u32,
+        pub neighbor_root_history_size: u32,
         pub max_deposit_amt: Balance,
         pub min_withdraw_amt: Balance,
         pub max_ext_amt: Balance,
         pub max_fee: Balance,
-        pub tokenwrapper_addr: AccountId,
-        pub handler: AccountId,
+        /// The account allowed to register verifying keys on the VAnchor's verifier
+        pub verifier_governor: AccountId,
         pub version: u32,
-        pub poseidon_contract_hash: Hash,
+        pub poseidon_contract_hash: Option<Hash>,
+        pub verifier_contract_hash: Hash,
         pub token_wrapper_contract_hash: Hash,
     }
 
+    #[ink(event)]
+    pub struct ResourceAdded {
+        #[ink(topic)]
+        resource_id: [u8; 32],
+        #[ink(topic)]
+        contract_address: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ResourceRemoved {
+        #[ink(topic)]
+        resource_id: [u8; 32],
+        #[ink(topic)]
+        contract_address: AccountId,
+    }
+
     /// The token wrapper handler error types.
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -90,6 +126,8 @@ mod anchor_handler {
         UpdateEdgeError,
         /// Set Handler Error
         SetHandlerError,
+        /// Proposal Decode Error
+        ProposalDecodeError,
     }
 
     impl AnchorHandler {
@@ -108,15 +146,19 @@ mod anchor_handler {
                 vanchor_data.max_edges,
                 vanchor_data.chain_id,
                 vanchor_data.levels,
+                vanchor_data.root_history_size,
+                vanchor_data.neighbor_root_history_size,
                 vanchor_data.max_deposit_amt,
                 vanchor_data.min_withdraw_amt,
                 vanchor_data.max_ext_amt,
                 vanchor_data.max_fee,
-                vanchor_data.tokenwrapper_addr,
-                vanchor_data.handler,
+                // the VAnchor only accepts proposals executed through this handler
+                Self::env().account_id(),
+                vanchor_data.verifier_governor,
                 token_wrapper_data,
                 vanchor_data.version,
                 vanchor_data.poseidon_contract_hash,
+                vanchor_data.verifier_contract_hash,
                 vanchor_data.token_wrapper_contract_hash,
             )
             .endowment(0)
@@ -132,6 +174,7 @@ mod anchor_handler {
             ink_lang::codegen::initialize_contract(|instance: &mut Self| {
                 instance.vanchor = vanchor;
                 instance.bridge_address = bridge_address;
+                instance.admin = Self::env().caller();
                 if initial_resource_ids.len() != initial_contract_addresses.len() {
                     panic!("initial_resource_ids and initial_contract_addresses len mismatch");
                 }
@@ -140,30 +183,75 @@ mod anchor_handler {
                     let resource_id = initial_resource_ids[i];
                     let contract_address = initial_contract_addresses[i];
 
-                    instance.set_resource(resource_id, contract_address);
+                    instance.add_resource(resource_id, contract_address);
                 }
             })
         }
 
-        /// Sets the resource_ids and addresses
+        /// Sets the resource_ids and addresses, only callable by the bridge or the admin
         ///
         /// * `resource_id` -  The resource id to be mapped to.
         /// * `contract_address` -  The contract address to be mapped to
         #[ink(message, selector = 1)]
-        pub fn set_resource(&mut self, resource_id: [u8; 32], contract_address: AccountId) {
-            let message = ink_prelude::format!("caller HANDLER is {:?}", self.env().caller());
-            ink_env::debug_println!("{}", message);
+        pub fn set_resource(
+            &mut self,
+            resource_id: [u8; 32],
+            contract_address: AccountId,
+        ) -> Result<()> {
+            if !self.is_bridge_or_admin() {
+                return Err(Error::Unauthorized);
+            }
 
-            let message =
-                ink_prelude::format!("contract address is  {:?}", self.env().account_id());
-            ink_env::debug_println!("{}", message);
+            self.add_resource(resource_id, contract_address);
 
-            self.resource_id_to_contract_address
-                .insert(resource_id, &contract_address);
+            Ok(())
+        }
+
+        /// Removes the resource_id and un-whitelists its contract address, only callable by the bridge or the admin
+        ///
+        /// * `resource_id` -  The resource id to be removed
+        #[ink(message)]
+        pub fn unset_resource(&mut self, resource_id: [u8; 32]) -> Result<()> {
+            if !self.is_bridge_or_admin() {
+                return Err(Error::Unauthorized);
+            }
+
+            let contract_address = self
+                .resource_id_to_contract_address
+                .get(resource_id)
+                .ok_or(Error::InvalidResourceId)?;
+
+            self.resource_id_to_contract_address.remove(resource_id);
             self.contract_address_to_resource_id
-                .insert(contract_address.clone(), &resource_id);
-            self.contract_whitelist
-                .insert(contract_address.clone(), &true);
+                .remove(contract_address);
+            self.contract_whitelist.insert(contract_address, &false);
+
+            EmitEvent::<AnchorHandler>::emit_event(
+                self.env(),
+                ResourceRemoved {
+                    resource_id,
+                    contract_address,
+                },
+            );
+
+            Ok(())
+        }
+
+        /// Gives up the admin rights, resources can then only be set by the bridge
+        #[ink(message)]
+        pub fn renounce_admin(&mut self) -> Result<()> {
+            if self.env().caller() != self.admin {
+                return Err(Error::Unauthorized);
+            }
+            self.admin = ZERO_ADDRESS.into();
+
+            Ok(())
+        }
+
+        /// Gets admin address
+        #[ink(message)]
+        pub fn get_admin(&self) -> Result<AccountId> {
+            Ok(self.admin)
         }
 
         /// Sets the bridge address
@@ -185,14 +273,15 @@ mod anchor_handler {
         /// * `data` - The data to execute
         #[ink(message, selector = 2)]
         pub fn execute_proposal(&mut self, resource_id: [u8; 32], data: Vec<u8>) -> Result<()> {
-            // Parse the (proposal)`data`.
-            let parsed_resource_id = element_encoder(&data[0..32]);
-
             if self.env().caller() != self.bridge_address {
                 return Err(Error::Unauthorized);
             }
 
-            if parsed_resource_id != resource_id {
+            // Parse the (proposal)`data`.
+            let (header, arguments) =
+                decode_proposal(&data).map_err(|_| Error::ProposalDecodeError)?;
+
+            if header.resource_id != resource_id {
                 return Err(Error::InvalidResourceId);
             }
 
@@ -209,37 +298,44 @@ mod anchor_handler {
                 return Err(Error::UnWhitelistedContractAddress);
             }
 
-            // extract function signature
-            let function_signature = element_encoder_for_four_bytes(&data[32..36]);
-            let arguments = &data[36..];
-            self.execute_function_signature(
-                function_signature,
-                arguments,
-                anchor_address.unwrap(),
-            )?;
+            let mut record =
+                self.execute_function_signature(header, arguments, anchor_address.unwrap())?;
+            record.proposal_hash = blake2b_256_32_bytes_output(&data);
+            self.store_update_record(&record);
 
             Ok(())
         }
 
         /// Executes the function signature
         ///
-        /// * `function_signature` -  The signature to be interpreted and executed on the vanchor contract
+        /// * `header` -  The proposal header, its function signature is interpreted and executed on the vanchor contract
         /// * `arguments` - The function arguments to be passed to respective functions in the vanchor contract
+        /// * `anchor_address` - The vanchor contract address
+        ///
+        /// Returns the update record of the executed proposal, without the proposal hash
         pub fn execute_function_signature(
             &mut self,
-            function_signature: [u8; 4],
+            header: ProposalHeader,
             arguments: &[u8],
             anchor_address: AccountId,
-        ) -> Result<()> {
+        ) -> Result<UpdateRecord> {
+            let function_signature = header.function_signature;
+            let mut record = UpdateRecord {
+                token_address: anchor_address,
+                src_chain_id: typed_chain_id(&header.resource_id),
+                resource_id: header.resource_id,
+                function_signature,
+                ..Default::default()
+            };
+
             if function_signature
                 == blake2b_256_4_bytes_output(b"VAnchor::set_handler".to_vec().as_slice())
             {
-                let nonce_bytes: [u8; 8] = element_encoder_for_eight_bytes(&arguments[0..8]);
-                let token_address_bytes: [u8; 32] = element_encoder(&arguments[8..40]);
+                let proposal = AnchorSetHandlerProposal::decode(arguments)
+                    .map_err(|_| Error::ProposalDecodeError)?;
 
-                let nonce = u64::from_be_bytes(nonce_bytes);
-
-                let token_address: AccountId = token_address_bytes.into();
+                let nonce = u64::from(header.nonce);
+                let token_address: AccountId = proposal.handler.into();
 
                 let cross_contract_call = build_call::<DefaultEnvironment>()
                     .call_type(Call::new().callee(anchor_address).gas_limit(50000000000))
@@ -258,30 +354,33 @@ mod anchor_handler {
             } else if function_signature
                 == blake2b_256_4_bytes_output(b"VAnchor::update_edge".to_vec().as_slice())
             {
-                let src_chain_id_bytes: [u8; 8] = element_encoder_for_eight_bytes(&arguments[0..8]);
-                let root: [u8; 32] = element_encoder(&arguments[8..40]);
-                let latest_leaf_index_bytes: [u8; 4] =
-                    element_encoder_for_four_bytes(&arguments[40..44]);
-                let target: [u8; 32] = element_encoder(&arguments[44..76]);
-
-                let src_chain_id = u64::from_be_bytes(src_chain_id_bytes);
-                let latest_leaf_index = u32::from_be_bytes(latest_leaf_index_bytes);
+                let proposal = AnchorUpdateProposal::decode(arguments)
+                    .map_err(|_| Error::ProposalDecodeError)?;
 
                 if self
                     .vanchor
-                    .update_edge(src_chain_id, root, latest_leaf_index, target)
+                    .update_edge(
+                        proposal.src_chain_id,
+                        proposal.merkle_root,
+                        proposal.latest_leaf_index,
+                        proposal.target,
+                    )
                     .is_err()
                 {
                     return Err(Error::UpdateEdgeError);
                 }
+
+                record.src_chain_id = proposal.src_chain_id;
+                record.merkle_root = proposal.merkle_root;
+                record.leaf_id = proposal.latest_leaf_index;
             } else if function_signature
                 == blake2b_256_4_bytes_output(
                     b"VAnchor::configure_max_deposit_limit".to_vec().as_slice(),
                 )
             {
-                let amount_bytes: [u8; 16] = element_encoder_for_sixteen_bytes(&arguments[0..16]);
-
-                let amount = u128::from_be_bytes(amount_bytes);
+                let amount = AnchorLimitProposal::decode(arguments)
+                    .map_err(|_| Error::ProposalDecodeError)?
+                    .amount;
 
                 if self
                     .vanchor
@@ -297,9 +396,9 @@ mod anchor_handler {
                         .as_slice(),
                 )
             {
-                let amount_bytes: [u8; 16] = element_encoder_for_sixteen_bytes(&arguments[0..16]);
-
-                let amount = u128::from_be_bytes(amount_bytes);
+                let amount = AnchorLimitProposal::decode(arguments)
+                    .map_err(|_| Error::ProposalDecodeError)?
+                    .amount;
 
                 if self
                     .vanchor
@@ -311,20 +410,65 @@ mod anchor_handler {
             } else {
                 return Err(Error::InvalidFunctionSignature);
             }
-            Ok(())
+            Ok(record)
         }
 
         /// Gets update record
         ///
         /// * `src_chain_id` -  The src_chain_id to query
-        /// * `height` - The leaf height to query
+        /// * `sequence` - The position of the record in the execution order of the chain
         #[ink(message)]
-        pub fn read_update_record(&self, src_chain_id: u64, height: u64) -> Result<UpdateRecord> {
-            if self.update_records.get((src_chain_id, height)).is_none() {
+        pub fn read_update_record(&self, src_chain_id: u64, sequence: u64) -> Result<UpdateRecord> {
+            if self.update_records.get((src_chain_id, sequence)).is_none() {
                 return Err(Error::UpdateRecordNotFound);
             }
 
-            Ok(self.update_records.get((src_chain_id, height)).unwrap())
+            Ok(self.update_records.get((src_chain_id, sequence)).unwrap())
+        }
+
+        /// Gets the latest update record of a chain
+        ///
+        /// * `src_chain_id` -  The chain id to query
+        #[ink(message)]
+        pub fn read_latest_update_record(&self, src_chain_id: u64) -> Result<UpdateRecord> {
+            let count = self.get_update_record_count(src_chain_id);
+            if count == 0 {
+                return Err(Error::UpdateRecordNotFound);
+            }
+
+            self.read_update_record(src_chain_id, count - 1)
+        }
+
+        /// Gets the update records of a chain, in execution order
+        ///
+        /// * `src_chain_id` -  The chain id to query
+        /// * `from` - The sequence of the first record
+        /// * `limit` - The maximum number of records, capped at `MAX_UPDATE_RECORDS_PER_PAGE`
+        #[ink(message)]
+        pub fn read_update_records(
+            &self,
+            src_chain_id: u64,
+            from: u64,
+            limit: u32,
+        ) -> Vec<UpdateRecord> {
+            let limit = limit.min(MAX_UPDATE_RECORDS_PER_PAGE) as u64;
+            let to = self
+                .get_update_record_count(src_chain_id)
+                .min(from.saturating_add(limit));
+
+            (from..to)
+                .filter_map(|sequence| self.read_update_record(src_chain_id, sequence).ok())
+                .collect()
+        }
+
+        /// Gets the number of update records of a chain
+        ///
+        /// * `src_chain_id` -  The chain id to query
+        #[ink(message)]
+        pub fn get_update_record_count(&self, src_chain_id: u64) -> u64 {
+            self.update_record_counts
+                .get(src_chain_id)
+                .unwrap_or_default()
         }
 
         /// Gets bridge address
@@ -333,6 +477,12 @@ mod anchor_handler {
             Ok(self.bridge_address)
         }
 
+        /// Gets the address of the VAnchor contract the proposals are executed on
+        #[ink(message)]
+        pub fn get_vanchor_address(&self) -> Result<AccountId> {
+            Ok(self.vanchor.to_account_id())
+        }
+
         /// Queries contract address
         ///
         /// * `resource_id` -  The resource_id to query
@@ -425,7 +575,7 @@ mod anchor_handler {
             &self,
             resource_id: [u8; 32],
             function_signature: [u8; 4],
-            nonce: [u8; 8],
+            nonce: [u8; 4],
             handler: AccountId,
         ) -> Result<Vec<u8>> {
             let mut result: Vec<u8> = [
@@ -444,6 +594,7 @@ mod anchor_handler {
             &self,
             resource_id: [u8; 32],
             function_signature: [u8; 4],
+            nonce: [u8; 4],
             src_chain_id: [u8; 8],
             root: [u8; 32],
             last_leaf_index: [u8; 4],
@@ -452,6 +603,7 @@ mod anchor_handler {
             let mut result: Vec<u8> = [
                 resource_id.as_slice(),
                 function_signature.as_slice(),
+                nonce.as_slice(),
                 src_chain_id.as_slice(),
                 root.as_slice(),
                 last_leaf_index.as_slice(),
@@ -467,16 +619,50 @@ mod anchor_handler {
             &self,
             resource_id: [u8; 32],
             function_signature: [u8; 4],
+            nonce: [u8; 4],
             amount: [u8; 16],
         ) -> Result<Vec<u8>> {
             let mut result: Vec<u8> = [
                 resource_id.as_slice(),
                 function_signature.as_slice(),
+                nonce.as_slice(),
                 amount.as_slice(),
             ]
             .concat();
 
             Ok(result)
         }
+
+        /// Appends an update record to the records of its chain
+        ///
+        /// * `record` - The record of an executed proposal
+        fn store_update_record(&mut self, record: &UpdateRecord) {
+            let sequence = self.get_update_record_count(record.src_chain_id);
+            self.update_records
+                .insert((record.src_chain_id, sequence), record);
+            self.update_record_counts
+                .insert(record.src_chain_id, &(sequence + 1));
+        }
+
+        fn add_resource(&mut self, resource_id: [u8; 32], contract_address: AccountId) {
+            self.resource_id_to_contract_address
+                .insert(resource_id, &contract_address);
+            self.contract_address_to_resource_id
+                .insert(contract_address, &resource_id);
+            self.contract_whitelist.insert(contract_address, &true);
+
+            EmitEvent::<AnchorHandler>::emit_event(
+                self.env(),
+                ResourceAdded {
+                    resource_id,
+                    contract_address,
+                },
+            );
+        }
+
+        fn is_bridge_or_admin(&self) -> bool {
+            let caller = self.env().caller();
+            caller == self.bridge_address || caller == self.admin
+        }
     }
 }
@@ -17,7 +17,10 @@ pub mod mixer {
     use scale::Encode;
     use verifier::MixerVerifierRef;
 
-    pub const ROOT_HISTORY_SIZE: u32 = 100;
+    /// The bounds of a root history size, every root costs a storage slot and a lookup when
+    /// checking withdrawals
+    pub const MIN_ROOT_HISTORY_SIZE: u32 = 1;
+    pub const MAX_ROOT_HISTORY_SIZE: u32 = 1024;
     pub const ERROR_MSG: &'static str =
         "requested transfer failed. this can be the case if the contract does not\
     have sufficient free funds or if the transfer would have brought the\
@@ -26,6 +29,8 @@ pub mod mixer {
     #[ink(storage)]
     #[derive(SpreadAllocate)]
     pub struct Mixer {
+        /// The account allowed to configure the mixer
+        creator: AccountId,
         deposit_size: Balance,
         merkle_tree: merkle_tree::MerkleTree,
         used_nullifiers: Mapping<[u8; 32], bool>,
@@ -68,6 +73,10 @@ pub mod mixer {
         NullifierKnown,
         /// Invalid Withdraw Proof
         InvalidWithdrawProof,
+        /// Unauthorized
+        Unauthorized,
+        /// Invalid root history size
+        InvalidRootHistorySize,
     }
 
     /// The mixer result type.
@@ -89,11 +98,17 @@ pub mod mixer {
         #[ink(constructor)]
         pub fn new(
             levels: u32,
+            root_history_size: u32,
             deposit_size: Balance,
             version: u32,
             poseidon_contract_hash: Hash,
             verifier_contract_hash: Hash,
         ) -> Self {
+            assert!(
+                is_valid_root_history_size(root_history_size),
+                "Root history size is out of bounds"
+            );
+
             let salt = version.to_le_bytes();
             let poseidon = PoseidonRef::new()
                 .endowment(0)
@@ -117,10 +132,12 @@ pub mod mixer {
                 });
 
             ink_lang::utils::initialize_contract(|contract: &mut Mixer| {
+                contract.creator = Self::env().caller();
                 contract.deposit_size = deposit_size;
                 contract.poseidon = poseidon;
                 contract.verifier = verifier;
                 contract.merkle_tree.levels = levels;
+                contract.merkle_tree.root_history_size = root_history_size;
                 contract.merkle_tree.current_root_index = 0;
                 contract.merkle_tree.next_index = 0;
 
@@ -138,6 +155,36 @@ pub mod mixer {
             self.merkle_tree.levels
         }
 
+        /// Returns the `root_history_size` value.
+        #[ink(message)]
+        pub fn root_history_size(&self) -> u32 {
+            self.merkle_tree.root_history_size
+        }
+
+        /// Sets the number of latest roots that are accepted in withdrawals
+        ///
+        /// * `root_history_size` - The new root history size
+        #[ink(message)]
+        pub fn configure_root_history_size(&mut self, root_history_size: u32) -> Result<()> {
+            if self.creator != self.env().caller() {
+                return Err(Error::Unauthorized);
+            }
+
+            if !is_valid_root_history_size(root_history_size) {
+                return Err(Error::InvalidRootHistorySize);
+            }
+
+            self.merkle_tree.set_root_history_size(root_history_size);
+
+            Ok(())
+        }
+
+        /// Returns the number of deposits the merkle tree can still hold.
+        #[ink(message)]
+        pub fn remaining_capacity(&self) -> u64 {
+            self.merkle_tree.remaining_capacity()
+        }
+
         /// Returns the `deposit_size` value.
         #[ink(message)]
         pub fn deposit_size(&self) -> Balance {
@@ -151,7 +198,7 @@ pub mod mixer {
                 "Deposit size is not correct"
             );
 
-            let index = self.merkle_tree.insert(self.poseidon.clone(), commitment);
+            let index = self.merkle_tree.insert(self.poseidon.clone(), commitment)?;
 
             self.env().emit_event(Deposit {
                 from: self.env().caller(),
@@ -159,7 +206,7 @@ pub mod mixer {
                 value: self.env().transferred_value(),
             });
 
-            index
+            Ok(index)
         }
 
         #[ink(message, payable)]
@@ -282,4 +329,8 @@ pub mod mixer {
             self.env().balance()
         }
     }
+
+    fn is_valid_root_history_size(root_history_size: u32) -> bool {
+        (MIN_ROOT_HISTORY_SIZE..=MAX_ROOT_HISTORY_SIZE).contains(&root_history_size)
+    }
 }
@@ -1,4 +1,4 @@
-use super::mixer::{Result, ROOT_HISTORY_SIZE};
+use super::mixer::Result;
 use super::*;
 #[cfg(feature = "std")]
 use ink_storage::traits::StorageLayout;
@@ -7,12 +7,15 @@ use ink_storage::Mapping;
 use poseidon::PoseidonRef;
 
 use ink_prelude::vec;
+use protocol_ink_lib::root_history;
 use protocol_ink_lib::zeroes::zeroes;
 
 #[derive(Default, Debug, SpreadLayout, SpreadAllocate)]
 #[cfg_attr(feature = "std", derive(StorageLayout))]
 pub struct MerkleTree {
     pub levels: u32,
+    /// The number of latest roots that are accepted in withdrawals
+    pub root_history_size: u32,
     pub current_root_index: u32,
     pub next_index: u32,
     pub filled_subtrees: Mapping<u32, [u8; 32]>,
@@ -31,11 +34,11 @@ impl MerkleTree {
     }
 
     pub fn insert(&mut self, hasher: PoseidonRef, leaf: [u8; 32]) -> Result<u32> {
+        if self.remaining_capacity() == 0 {
+            return Err(mixer::Error::MerkleTreeIsFull);
+        }
+
         let next_index = self.next_index;
-        assert!(
-            next_index != 2u32.pow(self.levels as u32),
-            "Merkle tree is full"
-        );
 
         let mut current_index = next_index;
         let mut current_level_hash = leaf;
@@ -56,39 +59,50 @@ impl MerkleTree {
             current_index = current_index / 2;
         }
 
-        let new_root_index = (self.current_root_index + 1) % ROOT_HISTORY_SIZE;
+        let new_root_index = (self.current_root_index + 1) % self.root_history_size;
         self.current_root_index = new_root_index;
         self.roots.insert(new_root_index, &current_level_hash);
         self.next_index = next_index + 1;
         Ok(next_index)
     }
 
-    pub fn is_known_root(&self, root: [u8; 32]) -> bool {
-        let message = ink_prelude::format!("root is {:?}", root);
-        ink_env::debug_println!("{}", &message);
-
-        if root == [0u8; 32] {
-            return false;
-        }
+    /// Returns the number of leaves the tree can hold
+    pub fn capacity(&self) -> u64 {
+        1u64.checked_shl(self.levels).unwrap_or(u64::MAX)
+    }
 
-        let mut i = self.current_root_index;
-        for _ in 0..ROOT_HISTORY_SIZE {
-            if let Some(r) = self.roots.get(&i) {
-                let message = ink_prelude::format!("root in history is {:?}", r);
-                ink_env::debug_println!("{}", &message);
+    /// Returns the number of leaves that can still be inserted
+    pub fn remaining_capacity(&self) -> u64 {
+        // `next_index` is a u32, so no more than u32::MAX leaves can ever be inserted
+        self.capacity()
+            .min(u32::MAX.into())
+            .saturating_sub(self.next_index.into())
+    }
 
-                if r == root {
-                    return true;
-                }
+    /// Resizes the root history, keeping as many of the latest roots as fit
+    ///
+    /// * `root_history_size` - The new root history size, validated by the caller
+    pub fn set_root_history_size(&mut self, root_history_size: u32) {
+        self.current_root_index = root_history::resize(
+            &mut self.roots,
+            |i| i,
+            self.current_root_index,
+            self.root_history_size,
+            root_history_size,
+        );
+        self.root_history_size = root_history_size;
+    }
 
-                if i == 0 {
-                    i = ROOT_HISTORY_SIZE - 1;
-                } else {
-                    i -= 1;
-                }
-            }
-        }
+    pub fn is_known_root(&self, root: [u8; 32]) -> bool {
+        let message = ink_prelude::format!("root is {:?}", root);
+        ink_env::debug_println!("{}", &message);
 
-        false
+        root_history::is_known_root(
+            &self.roots,
+            |i| i,
+            self.current_root_index,
+            self.root_history_size,
+            root,
+        )
     }
 }
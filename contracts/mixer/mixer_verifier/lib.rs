@@ -3,8 +3,8 @@
 use ink_env::call::FromAccountId;
 use ink_env::Environment;
 use ink_lang as ink;
-use ink_prelude::vec::Vec;
 use ink_storage::traits::SpreadAllocate;
+use protocol_ink_lib::verify_proof::VerifyProof;
 
 pub use self::mixer_verifier::{MixerVerifier, MixerVerifierRef};
 
@@ -14,30 +14,6 @@ impl SpreadAllocate for MixerVerifierRef {
     }
 }
 
-#[ink::chain_extension]
-pub trait VerifyProof {
-    type ErrorCode = VerifyProofErr;
-
-    #[ink(extension = 1101, returns_result = false)]
-    fn verify_proof(public_inp_bytes: (Vec<u8>, Vec<u8>)) -> bool;
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
-#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-pub enum VerifyProofErr {
-    FailGetVerifyProof,
-}
-
-impl ink_env::chain_extension::FromStatusCode for VerifyProofErr {
-    fn from_status_code(status_code: u32) -> Result<(), Self> {
-        match status_code {
-            0 => Ok(()),
-            1 => Err(Self::FailGetVerifyProof),
-            _ => panic!("encountered unknown status code"),
-        }
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum CustomEnvironment {}
@@ -94,7 +70,6 @@ mod verifier {
 
 #[ink::contract(env = crate::CustomEnvironment)]
 pub mod mixer_verifier {
-    use super::VerifyProofErr;
     use crate::verifier::ArkworksVerifierBn254;
     use ink_prelude::vec::Vec;
     use ink_storage::traits::SpreadAllocate;
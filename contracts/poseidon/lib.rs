@@ -3,7 +3,9 @@
 
 use ink_env::call::FromAccountId;
 use ink_lang as ink;
+use ink_prelude::vec;
 use ink_storage::traits::SpreadAllocate;
+use protocol_ink_lib::poseidon::{HashError, PoseidonHasher};
 
 pub use self::poseidon::{Poseidon, PoseidonRef};
 
@@ -13,6 +15,14 @@ impl SpreadAllocate for PoseidonRef {
     }
 }
 
+/// Hashes through a cross-contract call to the `Poseidon` contract
+impl PoseidonHasher for PoseidonRef {
+    fn hash_left_right(&self, left: [u8; 32], right: [u8; 32]) -> Result<[u8; 32], HashError> {
+        self.hash(vec![left, right])
+            .map_err(|_| HashError::HashError)
+    }
+}
+
 mod hasher {
     use ark_crypto_primitives::Error;
     use ark_ff::{BigInteger, PrimeField};
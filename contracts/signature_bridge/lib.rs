@@ -1,12 +1,14 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod signing;
+#[cfg(test)]
+mod tests;
 
 use ink_lang as ink;
 
 #[ink::contract]
 mod signature_bridge {
-    use super::signing::SignatureVerifier;
+    use super::signing::{Ed25519Verifier, SignatureScheme, SignatureVerifier, Sr25519Verifier};
     use crate::signing::SigningSystem;
     use ink_env::call::ExecutionInput;
     use ink_env::call::{build_call, Call, Selector};
@@ -18,11 +20,14 @@ mod signature_bridge {
     use ink_storage::{traits::SpreadAllocate, Mapping};
     use protocol_ink_lib::blake::blake2b_256_32_bytes_output;
     use protocol_ink_lib::keccak::Keccak256;
-    use protocol_ink_lib::utils::{element_encoder, truncate_and_pad};
+    use protocol_ink_lib::proposals::decode_proposal;
+    use protocol_ink_lib::utils::{truncate_and_pad, typed_chain_id};
     use webb_proposals::TypedChainId;
 
     /// The signature bridge result type.
     pub type Result<T> = core::result::Result<T, Error>;
+    /// The result of a handler message, the error is the index of the handler `Error` variant
+    type HandlerResult = core::result::Result<(), u8>;
 
     /// The token wrapper error types.
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -38,16 +43,64 @@ mod signature_bridge {
         WrongChainExecution,
         /// Invalid Resource Id
         InvalidResourceId,
+        /// Setting the resource on the handler failed
+        SetResourceError,
+        /// Executing the proposal on the handler failed
+        ExecuteProposalError,
+        /// Proposal data is too short to contain a proposal header
+        InvalidProposalData,
+        /// Proposal has already been executed
+        ProposalAlreadyExecuted,
+        /// Signature is malformed for the signature scheme of the governor
+        InvalidSignature,
+        /// New governor is not a public key of the signature scheme
+        InvalidGovernorKey,
     }
 
     #[ink(storage)]
     #[derive(SpreadAllocate)]
     pub struct SignatureBridge {
         governor: Vec<u8>,
+        /// the signature scheme of the governor
+        signature_scheme: SignatureScheme,
         proposal_nonce: u32,
+        /// nonce of the last governor rotation
+        refresh_nonce: u32,
         chain_id: u32,
         counts: Mapping<Vec<u8>, [u8; 32]>,
         resource_id_to_handler_address: Mapping<[u8; 32], AccountId>,
+        /// proposal hash => is executed
+        executed_proposals: Mapping<[u8; 32], bool>,
+    }
+
+    /// Emitted for every executed proposal
+    #[ink(event)]
+    pub struct ProposalExecuted {
+        #[ink(topic)]
+        resource_id: [u8; 32],
+        /// the nonce of the proposal header
+        nonce: u32,
+        /// the blake2b-256 hash of the proposal data
+        #[ink(topic)]
+        proposal_hash: [u8; 32],
+    }
+
+    /// Emitted when a resource is registered on its handler
+    #[ink(event)]
+    pub struct ResourceSet {
+        #[ink(topic)]
+        resource_id: [u8; 32],
+        #[ink(topic)]
+        handler_address: AccountId,
+        execution_context_address: AccountId,
+    }
+
+    /// Emitted when the governor is rotated by a signed transfer of ownership
+    #[ink(event)]
+    pub struct GovernanceOwnershipTransferred {
+        previous_governor: Vec<u8>,
+        new_governor: Vec<u8>,
+        nonce: u32,
     }
 
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -65,20 +118,92 @@ mod signature_bridge {
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub struct ExecuteProposalParams {
-        data: Vec<u8>,
-        sig: Vec<u8>,
+        pub data: Vec<u8>,
+        pub sig: Vec<u8>,
     }
 
     impl SignatureBridge {
+        /// Instantiates the bridge with an ECDSA governor
+        ///
+        /// Panics if `governor` is neither an Ethereum address nor a secp256k1 public key
         #[ink(constructor)]
         pub fn new(governor: Vec<u8>, proposal_nonce: u32, chain_id: u32) -> Self {
+            Self::new_with_signature_scheme(
+                governor,
+                SignatureScheme::Ecdsa,
+                proposal_nonce,
+                chain_id,
+            )
+        }
+
+        /// Instantiates the bridge with a governor of the given signature scheme
+        ///
+        /// Panics if `governor` is not a public key of `signature_scheme`
+        ///
+        /// * `governor` -  The governor public key
+        /// * `signature_scheme` - The signature scheme the governor signs with
+        /// * `proposal_nonce` - The initial proposal nonce
+        /// * `chain_id` - The chain id of this chain
+        #[ink(constructor)]
+        pub fn new_with_signature_scheme(
+            governor: Vec<u8>,
+            signature_scheme: SignatureScheme,
+            proposal_nonce: u32,
+            chain_id: u32,
+        ) -> Self {
             ink_lang::codegen::initialize_contract(|instance: &mut Self| {
+                instance.signature_scheme = signature_scheme;
+                // a malformed governor could never sign and would lock the bridge
+                assert!(
+                    instance.is_valid_governor_key(&governor),
+                    "Governor is not a public key of the signature scheme"
+                );
                 instance.governor = governor;
                 instance.proposal_nonce = proposal_nonce;
                 instance.chain_id = chain_id;
             })
         }
 
+        /// Rotates the governor, signed by the current governor
+        ///
+        /// * `new_governor` -  The public key of the new governor
+        /// * `nonce` - The refresh nonce, must be exactly one more than the current one
+        /// * `sig` - The signature of `nonce ++ new_governor` by the current governor
+        #[ink(message)]
+        pub fn transfer_ownership_with_signature(
+            &mut self,
+            new_governor: Vec<u8>,
+            nonce: u32,
+            sig: Vec<u8>,
+        ) -> Result<()> {
+            if self.refresh_nonce.checked_add(1) != Some(nonce) {
+                return Err(Error::InvalidNonce);
+            }
+            // a malformed governor could never sign again and would lock the bridge
+            if !self.is_valid_governor_key(&new_governor) {
+                return Err(Error::InvalidGovernorKey);
+            }
+
+            let mut data: Vec<u8> = Vec::new();
+            data.extend_from_slice(&nonce.to_be_bytes());
+            data.extend_from_slice(&new_governor);
+
+            if !self.is_signed_by_governor(&data, &sig)? {
+                return Err(Error::InvalidSignatureFromGovernor);
+            }
+
+            let previous_governor = core::mem::replace(&mut self.governor, new_governor.clone());
+            self.refresh_nonce = nonce;
+
+            self.env().emit_event(GovernanceOwnershipTransferred {
+                previous_governor,
+                new_governor,
+                nonce,
+            });
+
+            Ok(())
+        }
+
         #[ink(message)]
         pub fn admin_set_resource_with_signature(
             &mut self,
@@ -96,9 +221,9 @@ mod signature_bridge {
                 truncate_and_pad(resource_params.execution_context_address.as_ref());
             data.extend_from_slice(&*execution_context_address_bytes);
 
-            let mut sig = resource_params.sig;
+            let sig = resource_params.sig;
 
-            if !self.is_signed_by_governor(&data, &sig) {
+            if !self.is_signed_by_governor(&data, &sig)? {
                 return Err(Error::InvalidSignatureFromGovernor);
             }
 
@@ -112,16 +237,9 @@ mod signature_bridge {
                 return Err(Error::InvalidFunctionSig);
             }
 
-            // Save the info of "resource_id -> handler(contract)" in this contract.
-            self.resource_id_to_handler_address.insert(
-                resource_params.new_resource_id,
-                &resource_params.handler_address,
-            );
-
-            self.proposal_nonce = resource_params.nonce;
-
             // makes a low level cross contract call with the use of a selector 1 which represents set_resource contract function
-            build_call::<DefaultEnvironment>()
+            // so that the handler maps "new_resource_id -> execution context(contract)"
+            let set_resource_result = build_call::<DefaultEnvironment>()
                 .call_type(
                     Call::new()
                         .callee(resource_params.handler_address)
@@ -129,12 +247,30 @@ mod signature_bridge {
                 )
                 .exec_input(
                     ExecutionInput::new(Selector::new([0, 0, 0, 1]))
-                        .push_arg(resource_params.resource_id)
-                        .push_arg(resource_params.handler_address),
+                        .push_arg(resource_params.new_resource_id)
+                        .push_arg(resource_params.execution_context_address),
                 )
-                .returns::<()>()
-                .fire()
-                .unwrap();
+                .returns::<HandlerResult>()
+                .fire();
+
+            match set_resource_result {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) | Err(_) => return Err(Error::SetResourceError),
+            }
+
+            // Save the info of "resource_id -> handler(contract)" in this contract.
+            self.resource_id_to_handler_address.insert(
+                resource_params.new_resource_id,
+                &resource_params.handler_address,
+            );
+
+            self.proposal_nonce = resource_params.nonce;
+
+            self.env().emit_event(ResourceSet {
+                resource_id: resource_params.new_resource_id,
+                handler_address: resource_params.handler_address,
+                execution_context_address: resource_params.execution_context_address,
+            });
 
             Ok(())
         }
@@ -145,22 +281,43 @@ mod signature_bridge {
             data: Vec<u8>,
             sig: Vec<u8>,
         ) -> Result<()> {
-            if !self.is_signed_by_governor(&data, &sig) {
+            self.execute_signed_proposal(data, sig)
+        }
+
+        /// Executes a batch of signed proposals, a failing proposal doesn't abort the batch
+        ///
+        /// * `proposals` -  The proposals data and their signatures
+        ///
+        /// Returns the result of each proposal, in the order they were given
+        #[ink(message)]
+        pub fn execute_proposals_with_signature(
+            &mut self,
+            proposals: Vec<ExecuteProposalParams>,
+        ) -> Vec<Result<()>> {
+            proposals
+                .into_iter()
+                .map(|proposal| self.execute_signed_proposal(proposal.data, proposal.sig))
+                .collect()
+        }
+
+        fn execute_signed_proposal(&mut self, data: Vec<u8>, sig: Vec<u8>) -> Result<()> {
+            if !self.is_signed_by_governor(&data, &sig)? {
                 return Err(Error::InvalidSignatureFromGovernor);
             }
 
-            // Parse resourceID from the data
-            let resource_id_bytes = &data[0..32];
-            let resource_id = element_encoder(resource_id_bytes);
+            // The data must contain at least the proposal header
+            let (header, _) = decode_proposal(&data).map_err(|_| Error::InvalidProposalData)?;
 
-            // Parse chain ID + chain type from the resource ID
-            let mut typed_chain_id_bytes: [u8; 6] = resource_id_bytes[26..32].try_into().unwrap();
+            // Each proposal can only be executed once
+            let proposal_hash = blake2b_256_32_bytes_output(&data);
+            if self.is_proposal_executed(proposal_hash) {
+                return Err(Error::ProposalAlreadyExecuted);
+            }
 
-            let mut padded_typed_chain_id_bytes: [u8; 8] = [0; 8];
-            padded_typed_chain_id_bytes[2..].copy_from_slice(&typed_chain_id_bytes[..]);
+            let resource_id = header.resource_id;
 
-            let execution_typed_chain =
-                TypedChainId::from(u64::from_be_bytes(padded_typed_chain_id_bytes));
+            // Parse chain ID + chain type from the resource ID
+            let execution_typed_chain = TypedChainId::from(typed_chain_id(&resource_id));
 
             let execution_chain_id_type: u64 = execution_typed_chain.chain_id();
 
@@ -175,7 +332,7 @@ mod signature_bridge {
             }
 
             // makes a low level cross contract call with the use of a selector 2 which represents execute_proposal contract function
-            build_call::<DefaultEnvironment>()
+            let execute_proposal_result = build_call::<DefaultEnvironment>()
                 .call_type(
                     Call::new()
                         .callee(handler_address.unwrap())
@@ -186,13 +343,51 @@ mod signature_bridge {
                         .push_arg(resource_id)
                         .push_arg(data),
                 )
-                .returns::<()>()
-                .fire()
-                .unwrap();
+                .returns::<HandlerResult>()
+                .fire();
+
+            match execute_proposal_result {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) | Err(_) => return Err(Error::ExecuteProposalError),
+            }
+
+            self.executed_proposals.insert(proposal_hash, &true);
+
+            self.env().emit_event(ProposalExecuted {
+                resource_id,
+                nonce: header.nonce,
+                proposal_hash,
+            });
 
             Ok(())
         }
 
+        /// Queries if a proposal has already been executed
+        ///
+        /// * `proposal_hash` -  The blake2b-256 hash of the proposal data
+        #[ink(message)]
+        pub fn is_proposal_executed(&self, proposal_hash: [u8; 32]) -> bool {
+            self.executed_proposals.get(proposal_hash).unwrap_or(false)
+        }
+
+        /// Returns the `governor` value.
+        #[ink(message)]
+        pub fn governor(&self) -> Vec<u8> {
+            self.governor.clone()
+        }
+
+        /// Returns the `signature_scheme` value.
+        #[ink(message)]
+        pub fn signature_scheme(&self) -> SignatureScheme {
+            self.signature_scheme
+        }
+
+        /// Returns the `refresh_nonce` value.
+        #[ink(message)]
+        pub fn refresh_nonce(&self) -> u32 {
+            self.refresh_nonce
+        }
+
         #[ink(message)]
         pub fn construct_data(
             &self,
@@ -207,7 +402,7 @@ mod signature_bridge {
             let execution_context_address_bytes =
                 truncate_and_pad(execution_context_address.as_ref());
 
-            let mut result: Vec<u8> = [
+            let result: Vec<u8> = [
                 resource_id.as_slice(),
                 function_signature.as_slice(),
                 nonce.as_slice(),
@@ -220,10 +415,24 @@ mod signature_bridge {
             Ok(result)
         }
 
-        fn is_signed_by_governor(&self, data: &[u8], sig: &[u8]) -> bool {
-            let result = SignatureVerifier::verify(&self.governor, data, sig)
-                .unwrap_or_else(|error| panic!("could not verify due to: {:?}", error));
-            result
+        fn is_signed_by_governor(&self, data: &[u8], sig: &[u8]) -> Result<bool> {
+            // a malformed signature must not trap, so that a batch keeps executing
+            match self.signature_scheme {
+                SignatureScheme::Ecdsa => SignatureVerifier::verify(&self.governor, data, sig)
+                    .map_err(|_| Error::InvalidSignature),
+                SignatureScheme::Sr25519 => Sr25519Verifier::verify(&self.governor, data, sig)
+                    .map_err(|_| Error::InvalidSignature),
+                SignatureScheme::Ed25519 => Ed25519Verifier::verify(&self.governor, data, sig)
+                    .map_err(|_| Error::InvalidSignature),
+            }
+        }
+
+        fn is_valid_governor_key(&self, key: &[u8]) -> bool {
+            match self.signature_scheme {
+                SignatureScheme::Ecdsa => SignatureVerifier::is_valid_pub_key(key),
+                SignatureScheme::Sr25519 => Sr25519Verifier::is_valid_pub_key(key),
+                SignatureScheme::Ed25519 => Ed25519Verifier::is_valid_pub_key(key),
+            }
         }
     }
 }
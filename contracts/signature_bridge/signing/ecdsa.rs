@@ -3,6 +3,9 @@ use ink_env::Error;
 use ink_prelude::vec::Vec;
 
 pub const SIGNATURE_LENGTH: usize = 65;
+pub const COMPRESSED_PUBLIC_KEY_LENGTH: usize = 33;
+pub const UNCOMPRESSED_PUBLIC_KEY_LENGTH: usize = 64;
+pub const ETH_ADDRESS_LENGTH: usize = 20;
 
 pub fn validate_ecdsa_signature(data: &[u8], signature: &[u8]) -> bool {
     if signature.len() == SIGNATURE_LENGTH {
@@ -36,3 +39,84 @@ pub fn recover_ecdsa_pub_key(data: &[u8], signature: &[u8]) -> Result<Vec<u8>, E
     }
     Err(Error::EcdsaRecoveryFailed)
 }
+
+/// Compresses an uncompressed public key, with or without the 0x04 prefix
+///
+/// Returns `None` if the key is not a point on the secp256k1 curve
+///
+/// * `public_key` - the 64 or 65 bytes uncompressed public key
+pub fn compress_ecdsa_pub_key(public_key: &[u8]) -> Option<[u8; COMPRESSED_PUBLIC_KEY_LENGTH]> {
+    let public_key = match public_key.len() {
+        UNCOMPRESSED_PUBLIC_KEY_LENGTH => public_key,
+        65 if public_key[0] == 0x04 => &public_key[1..],
+        _ => return None,
+    };
+
+    // the prefix encodes the parity of the y coordinate
+    let mut output = [0u8; COMPRESSED_PUBLIC_KEY_LENGTH];
+    output[0] = if public_key[UNCOMPRESSED_PUBLIC_KEY_LENGTH - 1] % 2 == 0 {
+        0x02
+    } else {
+        0x03
+    };
+    output[1..].copy_from_slice(&public_key[..32]);
+
+    // deriving the address decompresses the point, recovering y from x, so the address
+    // only matches the hash of the given key if its y coordinate is on the curve
+    let mut hash = <Keccak256 as HashOutput>::Type::default();
+    ink_env::hash_bytes::<Keccak256>(public_key, &mut hash);
+    match ecdsa_pub_key_to_eth_address(&output) {
+        Ok(address) if address[..] == hash[32 - ETH_ADDRESS_LENGTH..] => Some(output),
+        _ => None,
+    }
+}
+
+/// Returns the Ethereum address of a compressed public key
+///
+/// * `public_key` - the 33 bytes compressed public key
+pub fn ecdsa_pub_key_to_eth_address(
+    public_key: &[u8; COMPRESSED_PUBLIC_KEY_LENGTH],
+) -> Result<[u8; ETH_ADDRESS_LENGTH], Error> {
+    let mut output = [0; ETH_ADDRESS_LENGTH];
+    ink_env::ecdsa_to_eth_address(public_key, &mut output)?;
+    Ok(output)
+}
+
+/// Checks if the governor is an Ethereum address or a public key on the secp256k1 curve
+///
+/// An address hides its public key, so only the zero address can be told apart from a
+/// valid one, no known key hashes to it
+///
+/// * `governor` - an Ethereum address, a compressed or an uncompressed public key
+pub fn is_valid_ecdsa_governor(governor: &[u8]) -> bool {
+    let public_key: [u8; COMPRESSED_PUBLIC_KEY_LENGTH] = match governor.len() {
+        ETH_ADDRESS_LENGTH => return governor != [0u8; ETH_ADDRESS_LENGTH],
+        COMPRESSED_PUBLIC_KEY_LENGTH => match governor.try_into() {
+            Ok(public_key) => public_key,
+            Err(_) => return false,
+        },
+        _ => match compress_ecdsa_pub_key(governor) {
+            Some(public_key) => public_key,
+            None => return false,
+        },
+    };
+
+    // deriving the address parses the point, which fails for keys off the curve
+    ecdsa_pub_key_to_eth_address(&public_key).is_ok()
+}
+
+/// Checks if a recovered public key belongs to the governor
+///
+/// * `governor` - an Ethereum address, a compressed or an uncompressed public key
+/// * `recovered_pub_key` - the 33 bytes compressed public key recovered from a signature
+pub fn is_ecdsa_governor(governor: &[u8], recovered_pub_key: &[u8]) -> Result<bool, Error> {
+    let recovered_pub_key: [u8; COMPRESSED_PUBLIC_KEY_LENGTH] = recovered_pub_key
+        .try_into()
+        .map_err(|_| Error::EcdsaRecoveryFailed)?;
+
+    match governor.len() {
+        ETH_ADDRESS_LENGTH => Ok(ecdsa_pub_key_to_eth_address(&recovered_pub_key)? == governor),
+        COMPRESSED_PUBLIC_KEY_LENGTH => Ok(recovered_pub_key == governor),
+        _ => Ok(compress_ecdsa_pub_key(governor) == Some(recovered_pub_key)),
+    }
+}
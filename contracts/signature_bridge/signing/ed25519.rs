@@ -0,0 +1,29 @@
+use super::VerifierError;
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+
+pub const PUBLIC_KEY_LENGTH: usize = 32;
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// Checks if the bytes decode to an ed25519 public key
+pub fn is_valid_ed25519_pub_key(public_key: &[u8]) -> bool {
+    public_key.len() == PUBLIC_KEY_LENGTH && PublicKey::from_bytes(public_key).is_ok()
+}
+
+pub fn verify_ed25519_signature(
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<bool, VerifierError> {
+    if public_key.len() != PUBLIC_KEY_LENGTH {
+        return Err(VerifierError::InvalidPublicKey);
+    }
+    if signature.len() != SIGNATURE_LENGTH {
+        return Err(VerifierError::InvalidSignature);
+    }
+
+    let public_key =
+        PublicKey::from_bytes(public_key).map_err(|_| VerifierError::InvalidPublicKey)?;
+    let signature = Signature::try_from(signature).map_err(|_| VerifierError::InvalidSignature)?;
+
+    Ok(public_key.verify(data, &signature).is_ok())
+}
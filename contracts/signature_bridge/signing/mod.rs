@@ -1,6 +1,23 @@
 use ink_prelude::vec::Vec;
+use ink_primitives::KeyPtr;
+#[cfg(feature = "std")]
+use ink_storage::traits::StorageLayout;
+use ink_storage::traits::{ExtKeyPtr, PackedLayout, SpreadAllocate, SpreadLayout};
 
 pub mod ecdsa;
+pub mod ed25519;
+pub mod sr25519;
+
+/// The error types of the signature schemes verified in Wasm
+#[derive(Debug)]
+pub enum VerifierError {
+    /// Invalid public key
+    InvalidPublicKey,
+    /// Invalid signature
+    InvalidSignature,
+    /// Public key recovery is not supported by the scheme
+    RecoveryNotSupported,
+}
 
 pub trait SigningSystem {
     type Error;
@@ -9,8 +26,34 @@ pub trait SigningSystem {
         let public_key = Self::recover_pub_key(msg, sig)?;
         Ok(public_key == *key)
     }
-    /// should return the uncompressed public key (64-bytes) without the 0x04 prefix
+    /// should return the public key that produced `sig`
     fn recover_pub_key(msg: &[u8], sig: &[u8]) -> Result<Vec<u8>, Self::Error>;
+    /// should return whether `key` is a well formed public key of the scheme
+    fn is_valid_pub_key(key: &[u8]) -> bool;
+}
+
+/// The signature scheme the governor signs proposals with
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout,
+)]
+#[cfg_attr(feature = "std", derive(StorageLayout, scale_info::TypeInfo))]
+pub enum SignatureScheme {
+    Ecdsa,
+    Sr25519,
+    Ed25519,
+}
+
+impl Default for SignatureScheme {
+    fn default() -> Self {
+        SignatureScheme::Ecdsa
+    }
+}
+
+impl SpreadAllocate for SignatureScheme {
+    fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+        ptr.next_for::<Self>();
+        Self::default()
+    }
 }
 
 pub struct SignatureVerifier;
@@ -18,8 +61,56 @@ pub struct SignatureVerifier;
 impl SigningSystem for SignatureVerifier {
     type Error = ink_env::Error;
 
-    /// returns the uncompressed public key(64bytes) without the 0x04 prefix
+    /// accepts a governor stored as an Ethereum address, a compressed or an uncompressed public key
+    fn verify(key: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, Self::Error> {
+        let public_key = Self::recover_pub_key(msg, sig)?;
+        ecdsa::is_ecdsa_governor(key, &public_key)
+    }
+
+    /// returns the compressed public key(33bytes)
     fn recover_pub_key(msg: &[u8], sig: &[u8]) -> Result<Vec<u8>, Self::Error> {
         ecdsa::recover_ecdsa_pub_key(msg, sig)
     }
+
+    fn is_valid_pub_key(key: &[u8]) -> bool {
+        ecdsa::is_valid_ecdsa_governor(key)
+    }
+}
+
+/// sr25519 signatures can't be used to recover the public key, so `verify` is overridden
+pub struct Sr25519Verifier;
+
+impl SigningSystem for Sr25519Verifier {
+    type Error = VerifierError;
+
+    fn verify(key: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, Self::Error> {
+        sr25519::verify_sr25519_signature(key, msg, sig)
+    }
+
+    fn recover_pub_key(_msg: &[u8], _sig: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        Err(VerifierError::RecoveryNotSupported)
+    }
+
+    fn is_valid_pub_key(key: &[u8]) -> bool {
+        sr25519::is_valid_sr25519_pub_key(key)
+    }
+}
+
+/// ed25519 signatures can't be used to recover the public key, so `verify` is overridden
+pub struct Ed25519Verifier;
+
+impl SigningSystem for Ed25519Verifier {
+    type Error = VerifierError;
+
+    fn verify(key: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, Self::Error> {
+        ed25519::verify_ed25519_signature(key, msg, sig)
+    }
+
+    fn recover_pub_key(_msg: &[u8], _sig: &[u8]) -> Result<Vec<u8>, Self::Error> {
+        Err(VerifierError::RecoveryNotSupported)
+    }
+
+    fn is_valid_pub_key(key: &[u8]) -> bool {
+        ed25519::is_valid_ed25519_pub_key(key)
+    }
 }
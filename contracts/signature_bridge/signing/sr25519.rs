@@ -0,0 +1,35 @@
+use super::VerifierError;
+use schnorrkel::{PublicKey, Signature};
+
+pub const PUBLIC_KEY_LENGTH: usize = 32;
+pub const SIGNATURE_LENGTH: usize = 64;
+/// The signing context substrate uses for sr25519 signatures
+pub const SIGNING_CONTEXT: &[u8] = b"substrate";
+
+/// Checks if the bytes decode to an sr25519 public key
+pub fn is_valid_sr25519_pub_key(public_key: &[u8]) -> bool {
+    public_key.len() == PUBLIC_KEY_LENGTH && PublicKey::from_bytes(public_key).is_ok()
+}
+
+// ink 3.3 does not expose `sr25519_verify`, so the signature is verified in Wasm.
+pub fn verify_sr25519_signature(
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<bool, VerifierError> {
+    if public_key.len() != PUBLIC_KEY_LENGTH {
+        return Err(VerifierError::InvalidPublicKey);
+    }
+    if signature.len() != SIGNATURE_LENGTH {
+        return Err(VerifierError::InvalidSignature);
+    }
+
+    let public_key =
+        PublicKey::from_bytes(public_key).map_err(|_| VerifierError::InvalidPublicKey)?;
+    let signature =
+        Signature::from_bytes(signature).map_err(|_| VerifierError::InvalidSignature)?;
+
+    Ok(public_key
+        .verify_simple(SIGNING_CONTEXT, data, &signature)
+        .is_ok())
+}
@@ -0,0 +1,432 @@
+use crate::signature_bridge::{Error, ExecuteProposalParams, SignatureBridge};
+use crate::signing::ecdsa::{compress_ecdsa_pub_key, is_ecdsa_governor};
+use crate::signing::sr25519::SIGNING_CONTEXT;
+use crate::signing::{
+    Ed25519Verifier, SignatureScheme, SignatureVerifier, SigningSystem, Sr25519Verifier,
+};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use ink_lang as ink;
+use ink_prelude::vec::Vec;
+use scale::Encode;
+
+const CHAIN_ID: u32 = 1;
+
+/// The secp256k1 public key of the private key 1, uncompressed without prefix
+const ECDSA_UNCOMPRESSED_KEY: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+/// The compressed form of `ECDSA_UNCOMPRESSED_KEY`
+const ECDSA_COMPRESSED_KEY: &str =
+    "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+/// The Ethereum address of `ECDSA_UNCOMPRESSED_KEY`
+const ECDSA_ETH_ADDRESS: &str = "7e5f4552091a69125d5dfcb7b8c2659029395bdf";
+/// The secp256k1 public key of the private key 2, uncompressed without prefix
+const OTHER_ECDSA_UNCOMPRESSED_KEY: &str = "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee51ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a";
+/// The compressed form of `OTHER_ECDSA_UNCOMPRESSED_KEY`
+const OTHER_ECDSA_COMPRESSED_KEY: &str =
+    "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5";
+/// The Ethereum address of `OTHER_ECDSA_UNCOMPRESSED_KEY`
+const OTHER_ECDSA_ETH_ADDRESS: &str = "2b5ad5c4795c026514f8317c7a215e218dccd6cf";
+/// The negation of `ECDSA_UNCOMPRESSED_KEY`, the same x with an odd y coordinate
+const ODD_ECDSA_UNCOMPRESSED_KEY: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798b7c52588d95c3b9aa25b0403f1eef75702e84bb7597aabe663b82f6f04ef2777";
+/// The message signed by the private key 1 in `ECDSA_SIGNATURE`
+const ECDSA_MESSAGE: &[u8] = b"webb signature bridge";
+/// The r || s || v signature of the keccak256 hash of `ECDSA_MESSAGE`
+const ECDSA_SIGNATURE: &str = "d47644539acec3da5e3ecf5fe8863c628a9c97e8b71e9ea9167a6f4f83c03c3243bd36782b2fd79e00a44851e393e1fbb5b8228ddbd71764b0cfa46ed3a8d55701";
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Returns the uncompressed key with the 0x04 prefix
+fn prefixed(uncompressed_key: &str) -> Vec<u8> {
+    let mut key = ink_prelude::vec![0x04];
+    key.extend_from_slice(&from_hex(uncompressed_key));
+    key
+}
+
+fn keypair(seed: u8) -> Keypair {
+    let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+    Keypair { secret, public }
+}
+
+/// Sets up a bridge governed by the ed25519 key of `keypair(1)`
+fn setup() -> SignatureBridge {
+    SignatureBridge::new_with_signature_scheme(
+        keypair(1).public.to_bytes().to_vec(),
+        SignatureScheme::Ed25519,
+        0,
+        CHAIN_ID,
+    )
+}
+
+/// A proposal for a resource without chain id, with a body of `body_length` bytes
+fn proposal_data(body_length: usize) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&[0u8; 32]);
+    data.extend_from_slice(&[0, 0, 0, 2]);
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(&ink_prelude::vec![9u8; body_length]);
+    data
+}
+
+fn sign(seed: u8, data: &[u8]) -> Vec<u8> {
+    keypair(seed).sign(data).to_bytes().to_vec()
+}
+
+#[ink::test]
+fn execute_proposal_fails_if_not_signed_by_governor() {
+    let mut bridge = setup();
+    let data = proposal_data(8);
+
+    assert_eq!(
+        bridge.execute_proposal_with_signature(data.clone(), sign(2, &data)),
+        Err(Error::InvalidSignatureFromGovernor)
+    );
+    // a malformed signature is rejected instead of trapping
+    assert_eq!(
+        bridge.execute_proposal_with_signature(data, ink_prelude::vec![0u8; 10]),
+        Err(Error::InvalidSignature)
+    );
+}
+
+#[ink::test]
+fn execute_proposal_decodes_the_shared_proposal_header() {
+    let mut bridge = setup();
+
+    let data = proposal_data(0)[..39].to_vec();
+    assert_eq!(
+        bridge.execute_proposal_with_signature(data.clone(), sign(1, &data)),
+        Err(Error::InvalidProposalData)
+    );
+
+    // a header without body passes decoding, the resource is on another chain
+    let data = proposal_data(0);
+    assert_eq!(
+        bridge.execute_proposal_with_signature(data.clone(), sign(1, &data)),
+        Err(Error::WrongChainExecution)
+    );
+}
+
+#[ink::test]
+fn execute_proposals_keeps_executing_after_a_failing_proposal() {
+    let mut bridge = setup();
+    let truncated = proposal_data(0)[..39].to_vec();
+    let valid = proposal_data(4);
+
+    let results = bridge.execute_proposals_with_signature(ink_prelude::vec![
+        ExecuteProposalParams {
+            sig: ink_prelude::vec![0u8; 10],
+            data: valid.clone(),
+        },
+        ExecuteProposalParams {
+            sig: sign(2, &valid),
+            data: valid.clone(),
+        },
+        ExecuteProposalParams {
+            sig: sign(1, &truncated),
+            data: truncated,
+        },
+        ExecuteProposalParams {
+            sig: sign(1, &valid),
+            data: valid,
+        },
+    ]);
+
+    // every proposal is checked on its own, in the order given
+    assert_eq!(
+        results,
+        ink_prelude::vec![
+            Err(Error::InvalidSignature),
+            Err(Error::InvalidSignatureFromGovernor),
+            Err(Error::InvalidProposalData),
+            Err(Error::WrongChainExecution),
+        ]
+    );
+}
+
+#[ink::test]
+fn compress_ecdsa_pub_key_works() {
+    let compressed = from_hex(ECDSA_COMPRESSED_KEY);
+
+    assert_eq!(
+        compress_ecdsa_pub_key(&from_hex(ECDSA_UNCOMPRESSED_KEY)).map(|key| key.to_vec()),
+        Some(compressed.clone())
+    );
+    assert_eq!(
+        compress_ecdsa_pub_key(&prefixed(ECDSA_UNCOMPRESSED_KEY)).map(|key| key.to_vec()),
+        Some(compressed)
+    );
+    // an odd y coordinate gets the 0x03 prefix
+    assert_eq!(
+        compress_ecdsa_pub_key(&from_hex(ODD_ECDSA_UNCOMPRESSED_KEY)).map(|key| key[0]),
+        Some(0x03)
+    );
+    // a y coordinate that doesn't belong to x is not on the curve
+    let mut off_curve_key = from_hex(ECDSA_UNCOMPRESSED_KEY);
+    off_curve_key[63] += 1;
+    assert_eq!(compress_ecdsa_pub_key(&off_curve_key), None);
+
+    // a 65 bytes key needs the 0x04 prefix, other lengths aren't uncompressed keys
+    let mut wrong_prefix = prefixed(ECDSA_UNCOMPRESSED_KEY);
+    wrong_prefix[0] = 0x02;
+    assert_eq!(compress_ecdsa_pub_key(&wrong_prefix), None);
+    assert_eq!(
+        compress_ecdsa_pub_key(&from_hex(ECDSA_COMPRESSED_KEY)),
+        None
+    );
+    assert_eq!(compress_ecdsa_pub_key(&[0u8; 63]), None);
+}
+
+#[ink::test]
+fn is_ecdsa_governor_accepts_every_key_form() {
+    let recovered = from_hex(ECDSA_COMPRESSED_KEY);
+
+    for governor in [
+        from_hex(ECDSA_COMPRESSED_KEY),
+        from_hex(ECDSA_UNCOMPRESSED_KEY),
+        prefixed(ECDSA_UNCOMPRESSED_KEY),
+        from_hex(ECDSA_ETH_ADDRESS),
+    ] {
+        assert_eq!(is_ecdsa_governor(&governor, &recovered), Ok(true));
+    }
+    for governor in [
+        from_hex(OTHER_ECDSA_COMPRESSED_KEY),
+        from_hex(OTHER_ECDSA_UNCOMPRESSED_KEY),
+        prefixed(OTHER_ECDSA_UNCOMPRESSED_KEY),
+        from_hex(OTHER_ECDSA_ETH_ADDRESS),
+    ] {
+        assert_eq!(is_ecdsa_governor(&governor, &recovered), Ok(false));
+    }
+
+    // the recovered key must be compressed
+    assert!(is_ecdsa_governor(
+        &from_hex(ECDSA_ETH_ADDRESS),
+        &from_hex(ECDSA_UNCOMPRESSED_KEY)
+    )
+    .is_err());
+}
+
+#[ink::test]
+fn ecdsa_signature_verifies_against_every_governor_form() {
+    let signature = from_hex(ECDSA_SIGNATURE);
+
+    for governor in [
+        from_hex(ECDSA_COMPRESSED_KEY),
+        from_hex(ECDSA_UNCOMPRESSED_KEY),
+        prefixed(ECDSA_UNCOMPRESSED_KEY),
+        from_hex(ECDSA_ETH_ADDRESS),
+    ] {
+        assert_eq!(
+            SignatureVerifier::verify(&governor, ECDSA_MESSAGE, &signature),
+            Ok(true)
+        );
+    }
+    assert_eq!(
+        SignatureVerifier::verify(
+            &from_hex(OTHER_ECDSA_ETH_ADDRESS),
+            ECDSA_MESSAGE,
+            &signature
+        ),
+        Ok(false)
+    );
+    assert_eq!(
+        SignatureVerifier::verify(&from_hex(ECDSA_ETH_ADDRESS), b"another message", &signature),
+        Ok(false)
+    );
+}
+
+/// Signs a governor rotation to `new_governor` with the ed25519 key of `keypair(seed)`
+fn sign_rotation(seed: u8, new_governor: &[u8], nonce: u32) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&nonce.to_be_bytes());
+    data.extend_from_slice(new_governor);
+    sign(seed, &data)
+}
+
+#[ink::test]
+fn transfer_ownership_with_signature_works() {
+    let mut bridge = setup();
+    let previous_governor = keypair(1).public.to_bytes().to_vec();
+    let new_governor = keypair(2).public.to_bytes().to_vec();
+
+    assert_eq!(
+        bridge.transfer_ownership_with_signature(
+            new_governor.clone(),
+            1,
+            sign_rotation(1, &new_governor, 1)
+        ),
+        Ok(())
+    );
+    assert_eq!(bridge.governor(), new_governor);
+    assert_eq!(bridge.refresh_nonce(), 1);
+
+    let events = ink_env::test::recorded_events().collect::<Vec<_>>();
+    assert_eq!(events.len(), 1);
+    // the first byte is the index of the event in the contract
+    assert_eq!(
+        events[0].data[1..],
+        (previous_governor, new_governor, 1u32).encode()
+    );
+
+    // the new governor signs from now on
+    let data = proposal_data(8);
+    assert_eq!(
+        bridge.execute_proposal_with_signature(data.clone(), sign(1, &data)),
+        Err(Error::InvalidSignatureFromGovernor)
+    );
+    assert_eq!(
+        bridge.execute_proposal_with_signature(data.clone(), sign(2, &data)),
+        Err(Error::WrongChainExecution)
+    );
+}
+
+#[ink::test]
+fn transfer_ownership_with_signature_fails_with_stale_or_skipped_nonce() {
+    let mut bridge = setup();
+    let new_governor = keypair(2).public.to_bytes().to_vec();
+
+    for nonce in [0, 2] {
+        assert_eq!(
+            bridge.transfer_ownership_with_signature(
+                new_governor.clone(),
+                nonce,
+                sign_rotation(1, &new_governor, nonce)
+            ),
+            Err(Error::InvalidNonce)
+        );
+    }
+
+    let signature = sign_rotation(1, &new_governor, 1);
+    assert_eq!(
+        bridge.transfer_ownership_with_signature(new_governor.clone(), 1, signature.clone()),
+        Ok(())
+    );
+    // the rotation can't be replayed
+    assert_eq!(
+        bridge.transfer_ownership_with_signature(new_governor, 1, signature),
+        Err(Error::InvalidNonce)
+    );
+    assert_eq!(bridge.refresh_nonce(), 1);
+}
+
+#[ink::test]
+fn transfer_ownership_with_signature_fails_if_not_signed_by_governor() {
+    let mut bridge = setup();
+    let new_governor = keypair(2).public.to_bytes().to_vec();
+
+    assert_eq!(
+        bridge.transfer_ownership_with_signature(
+            new_governor.clone(),
+            1,
+            sign_rotation(2, &new_governor, 1)
+        ),
+        Err(Error::InvalidSignatureFromGovernor)
+    );
+    assert_eq!(bridge.governor(), keypair(1).public.to_bytes().to_vec());
+    assert_eq!(bridge.refresh_nonce(), 0);
+    assert_eq!(ink_env::test::recorded_events().count(), 0);
+}
+
+#[ink::test]
+fn transfer_ownership_with_signature_fails_with_malformed_governor() {
+    let mut bridge = setup();
+    // an ecdsa key is too long for an ed25519 governor
+    let new_governor = from_hex(ECDSA_COMPRESSED_KEY);
+
+    assert_eq!(
+        bridge.transfer_ownership_with_signature(
+            new_governor.clone(),
+            1,
+            sign_rotation(1, &new_governor, 1)
+        ),
+        Err(Error::InvalidGovernorKey)
+    );
+    assert_eq!(bridge.governor(), keypair(1).public.to_bytes().to_vec());
+}
+
+#[ink::test]
+fn is_valid_pub_key_checks_the_encoding() {
+    assert!(SignatureVerifier::is_valid_pub_key(&from_hex(
+        ECDSA_ETH_ADDRESS
+    )));
+    assert!(SignatureVerifier::is_valid_pub_key(&from_hex(
+        ECDSA_COMPRESSED_KEY
+    )));
+    assert!(SignatureVerifier::is_valid_pub_key(&from_hex(
+        ECDSA_UNCOMPRESSED_KEY
+    )));
+    assert!(SignatureVerifier::is_valid_pub_key(&prefixed(
+        ECDSA_UNCOMPRESSED_KEY
+    )));
+    // 0x05 is not a compressed key prefix
+    let mut compressed = from_hex(ECDSA_COMPRESSED_KEY);
+    compressed[0] = 0x05;
+    assert!(!SignatureVerifier::is_valid_pub_key(&compressed));
+    assert!(!SignatureVerifier::is_valid_pub_key(&[0u8; 32]));
+    let mut off_curve_key = prefixed(ECDSA_UNCOMPRESSED_KEY);
+    off_curve_key[64] ^= 1;
+    assert!(!SignatureVerifier::is_valid_pub_key(&off_curve_key));
+    assert!(!SignatureVerifier::is_valid_pub_key(&[0u8; 20]));
+
+    let ed25519_key = keypair(1).public.to_bytes();
+    assert!(Ed25519Verifier::is_valid_pub_key(&ed25519_key));
+    assert!(!Ed25519Verifier::is_valid_pub_key(&ed25519_key[..31]));
+}
+
+#[ink::test]
+fn new_works_with_every_ecdsa_governor_form() {
+    for governor in [
+        from_hex(ECDSA_COMPRESSED_KEY),
+        from_hex(ECDSA_UNCOMPRESSED_KEY),
+        prefixed(ECDSA_UNCOMPRESSED_KEY),
+        from_hex(ECDSA_ETH_ADDRESS),
+    ] {
+        assert_eq!(
+            SignatureBridge::new(governor.clone(), 0, CHAIN_ID).governor(),
+            governor
+        );
+    }
+}
+
+#[ink::test]
+#[should_panic(expected = "Governor is not a public key of the signature scheme")]
+fn new_fails_with_off_curve_governor() {
+    let mut governor = from_hex(ECDSA_UNCOMPRESSED_KEY);
+    governor[63] ^= 1;
+    SignatureBridge::new(governor, 0, CHAIN_ID);
+}
+
+#[ink::test]
+#[should_panic(expected = "Governor is not a public key of the signature scheme")]
+fn new_with_signature_scheme_fails_with_malformed_governor() {
+    SignatureBridge::new_with_signature_scheme(
+        from_hex(ECDSA_COMPRESSED_KEY),
+        SignatureScheme::Ed25519,
+        0,
+        CHAIN_ID,
+    );
+}
+
+fn sr25519_keypair(seed: u8) -> schnorrkel::Keypair {
+    schnorrkel::MiniSecretKey::from_bytes(&[seed; 32])
+        .unwrap()
+        .expand_to_keypair(schnorrkel::ExpansionMode::Ed25519)
+}
+
+#[ink::test]
+fn sr25519_signature_verifies_under_the_substrate_context() {
+    let keypair = sr25519_keypair(1);
+    let public_key = keypair.public.to_bytes();
+    let data = proposal_data(8);
+    let signature = keypair.sign_simple(SIGNING_CONTEXT, &data).to_bytes();
+
+    assert!(Sr25519Verifier::verify(&public_key, &data, &signature).unwrap());
+    let other_public_key = sr25519_keypair(2).public.to_bytes();
+    assert!(!Sr25519Verifier::verify(&other_public_key, &data, &signature).unwrap());
+
+    let other_context_signature = keypair.sign_simple(b"webb", &data).to_bytes();
+    assert!(!Sr25519Verifier::verify(&public_key, &data, &other_context_signature).unwrap());
+}
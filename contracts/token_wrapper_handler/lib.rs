@@ -5,22 +5,27 @@ use ink_lang as ink;
 #[ink::contract]
 mod token_wrapper_handler {
     use governed_token_wrapper::GovernedTokenWrapperRef;
+    // the dependency contract implements `EmitEvent` too, so events name this contract explicitly
+    use ink_lang::codegen::EmitEvent;
     use ink_prelude::string::String;
     use ink_prelude::vec::Vec;
     use ink_storage::traits::{PackedLayout, SpreadLayout, StorageLayout};
     use ink_storage::{traits::SpreadAllocate, Mapping};
     use protocol_ink_lib::blake::blake2b_256_4_bytes_output;
     use protocol_ink_lib::keccak::Keccak256;
-    use protocol_ink_lib::utils::{
-        element_encoder, element_encoder_for_eight_bytes, element_encoder_for_four_bytes,
-        element_encoder_for_one_byte, element_encoder_for_two_bytes,
+    use protocol_ink_lib::proposals::{
+        decode_proposal, FeeRecipientProposal, ProposalBody, ProposalHeader, SetFeeProposal,
+        TokenAddressProposal,
     };
+    use protocol_ink_lib::utils::ZERO_ADDRESS;
 
     #[ink(storage)]
     #[derive(SpreadAllocate)]
     pub struct TokenWrapperHandler {
         /// Contract address of previously deployed Bridge.
         bridge_address: AccountId,
+        /// Account allowed to set resources while bootstrapping, zero once renounced
+        admin: AccountId,
         /// resourceID => token contract address
         resource_id_to_contract_address: Mapping<[u8; 32], AccountId>,
         /// Execution contract address => resourceID
@@ -34,6 +39,22 @@ mod token_wrapper_handler {
     /// The token wrapper handler result type.
     pub type Result<T> = core::result::Result<T, Error>;
 
+    #[ink(event)]
+    pub struct ResourceAdded {
+        #[ink(topic)]
+        resource_id: [u8; 32],
+        #[ink(topic)]
+        contract_address: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ResourceRemoved {
+        #[ink(topic)]
+        resource_id: [u8; 32],
+        #[ink(topic)]
+        contract_address: AccountId,
+    }
+
     /// The token wrapper handler error types.
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -56,6 +77,8 @@ mod token_wrapper_handler {
         AddTokenAddressError,
         /// Remove Token Address Error
         RemoveTokenAddressError,
+        /// Proposal Decode Error
+        ProposalDecodeError,
     }
 
     // Represents the token wrapper contract instantiation configs/data
@@ -119,6 +142,7 @@ mod token_wrapper_handler {
 
             ink_lang::codegen::initialize_contract(|instance: &mut Self| {
                 instance.bridge_address = bridge_address;
+                instance.admin = Self::env().caller();
                 instance.token_wrapper = token_wrapper;
 
                 if initial_resource_ids.len() != initial_contract_addresses.len() {
@@ -129,23 +153,75 @@ mod token_wrapper_handler {
                     let resource_id = initial_resource_ids[i];
                     let contract_address = initial_contract_addresses[i];
 
-                    instance.set_resource(resource_id, contract_address);
+                    instance.add_resource(resource_id, contract_address);
                 }
             })
         }
 
-        /// Sets the resource_ids and addresses
+        /// Sets the resource_ids and addresses, only callable by the bridge or the admin
         ///
         /// * `resource_id` -  The resource id to be mapped to.
         /// * `contract_address` -  The contract address to be mapped to
         #[ink(message, selector = 1)]
-        pub fn set_resource(&mut self, resource_id: [u8; 32], contract_address: AccountId) {
-            self.resource_id_to_contract_address
-                .insert(resource_id, &contract_address);
+        pub fn set_resource(
+            &mut self,
+            resource_id: [u8; 32],
+            contract_address: AccountId,
+        ) -> Result<()> {
+            if !self.is_bridge_or_admin() {
+                return Err(Error::Unauthorized);
+            }
+
+            self.add_resource(resource_id, contract_address);
+
+            Ok(())
+        }
+
+        /// Removes the resource_id and un-whitelists its contract address, only callable by the bridge or the admin
+        ///
+        /// * `resource_id` -  The resource id to be removed
+        #[ink(message)]
+        pub fn unset_resource(&mut self, resource_id: [u8; 32]) -> Result<()> {
+            if !self.is_bridge_or_admin() {
+                return Err(Error::Unauthorized);
+            }
+
+            let contract_address = self
+                .resource_id_to_contract_address
+                .get(resource_id)
+                .ok_or(Error::InvalidResourceId)?;
+
+            self.resource_id_to_contract_address.remove(resource_id);
             self.contract_address_to_resource_id
-                .insert(contract_address.clone(), &resource_id);
-            self.contract_whitelist
-                .insert(contract_address.clone(), &true);
+                .remove(contract_address);
+            self.contract_whitelist.insert(contract_address, &false);
+
+            EmitEvent::<TokenWrapperHandler>::emit_event(
+                self.env(),
+                ResourceRemoved {
+                    resource_id,
+                    contract_address,
+                },
+            );
+
+            Ok(())
+        }
+
+        /// Gives up the admin rights, resources can then only be set by the bridge
+        #[ink(message)]
+        pub fn renounce_admin(&mut self) -> Result<()> {
+            if self.env().caller() != self.admin {
+                return Err(Error::Unauthorized);
+            }
+            self.admin = ZERO_ADDRESS.into();
+
+            Ok(())
+        }
+
+        /// Gets admin address
+        #[ink(message)]
+        pub fn get_admin(&self) -> Result<AccountId> {
+            Ok(self.admin)
         }
 
         /// Sets the bridge address
@@ -167,14 +243,15 @@ mod token_wrapper_handler {
         /// * `data` - The data to execute
         #[ink(message, selector = 2)]
         pub fn execute_proposal(&mut self, resource_id: [u8; 32], data: Vec<u8>) -> Result<()> {
-            // Parse the (proposal)`data`.
-            let parsed_resource_id = element_encoder(&data[0..32]);
-
             if self.env().caller() != self.bridge_address {
                 return Err(Error::Unauthorized);
             }
 
-            if parsed_resource_id != resource_id {
+            // Parse the (proposal)`data`.
+            let (header, arguments) =
+                decode_proposal(&data).map_err(|_| Error::ProposalDecodeError)?;
+
+            if header.resource_id != resource_id {
                 return Err(Error::InvalidResourceId);
             }
 
@@ -192,33 +269,32 @@ mod token_wrapper_handler {
                 return Err(Error::UnWhitelistedContractAddress);
             }
 
-            // extract function signature
-            let function_signature = element_encoder_for_four_bytes(&data[32..36]);
-            let arguments = &data[36..];
-            self.execute_function_signature(function_signature, arguments);
+            self.execute_function_signature(header, arguments)?;
 
             Ok(())
         }
 
         /// Executes the function signature
         ///
-        /// * `function_signature` -  The signature to be interpreted and executed on the token-wrapper contract
+        /// * `header` -  The proposal header, its function signature is interpreted and executed on the token-wrapper contract
         /// * `arguments` - The function arguments to be passed to respective functions in the token-wrapper contract
         pub fn execute_function_signature(
             &mut self,
-            function_signature: [u8; 4],
+            header: ProposalHeader,
             arguments: &[u8],
         ) -> Result<()> {
+            let function_signature = header.function_signature;
             if function_signature
                 == blake2b_256_4_bytes_output(b"GovernedTokenWrapper::set_fee".to_vec().as_slice())
             {
-                let nonce_bytes: [u8; 4] = element_encoder_for_four_bytes(&arguments[0..4]);
-                let fee_bytes: [u8; 2] = element_encoder_for_two_bytes(&arguments[4..6]);
-
-                let nonce = u32::from_be_bytes(nonce_bytes);
-                let fee = u16::from_be_bytes(fee_bytes);
+                let proposal =
+                    SetFeeProposal::decode(arguments).map_err(|_| Error::ProposalDecodeError)?;
 
-                if self.token_wrapper.set_fee(fee.into(), nonce).is_err() {
+                if self
+                    .token_wrapper
+                    .set_fee(proposal.fee.into(), header.nonce)
+                    .is_err()
+                {
                     return Err(Error::SetFeeError);
                 }
             } else if function_signature
@@ -228,14 +304,12 @@ mod token_wrapper_handler {
                         .as_slice(),
                 )
             {
-                let nonce_bytes: [u8; 4] = element_encoder_for_four_bytes(&arguments[0..4]);
-                let token_address: [u8; 32] = element_encoder(&arguments[4..36]);
-
-                let nonce = u32::from_be_bytes(nonce_bytes);
+                let proposal = TokenAddressProposal::decode(arguments)
+                    .map_err(|_| Error::ProposalDecodeError)?;
 
                 if self
                     .token_wrapper
-                    .add_token_address(token_address.into(), nonce)
+                    .add_token_address(proposal.token_address.into(), header.nonce)
                     .is_err()
                 {
                     return Err(Error::AddTokenAddressError);
@@ -247,14 +321,12 @@ mod token_wrapper_handler {
                         .as_slice(),
                 )
             {
-                let nonce_bytes: [u8; 4] = element_encoder_for_four_bytes(&arguments[0..4]);
-                let token_address: [u8; 32] = element_encoder(&arguments[4..36]);
-
-                let nonce = u32::from_be_bytes(nonce_bytes);
+                let proposal = TokenAddressProposal::decode(arguments)
+                    .map_err(|_| Error::ProposalDecodeError)?;
 
                 if self
                     .token_wrapper
-                    .remove_token_address(token_address.into(), nonce)
+                    .remove_token_address(proposal.token_address.into(), header.nonce)
                     .is_err()
                 {
                     return Err(Error::RemoveTokenAddressError);
@@ -266,14 +338,12 @@ mod token_wrapper_handler {
                         .as_slice(),
                 )
             {
-                let nonce_bytes: [u8; 4] = element_encoder_for_four_bytes(&arguments[0..4]);
-                let fee_recipient: [u8; 32] = element_encoder(&arguments[4..36]);
-
-                let nonce = u32::from_be_bytes(nonce_bytes);
+                let proposal = FeeRecipientProposal::decode(arguments)
+                    .map_err(|_| Error::ProposalDecodeError)?;
 
                 if self
                     .token_wrapper
-                    .set_fee_recipient(fee_recipient.into(), nonce)
+                    .set_fee_recipient(proposal.fee_recipient.into(), header.nonce)
                     .is_err()
                 {
                     return Err(Error::SetFeeRecipientError);
@@ -419,5 +489,26 @@ mod token_wrapper_handler {
 
             Ok(result)
         }
+
+        fn add_resource(&mut self, resource_id: [u8; 32], contract_address: AccountId) {
+            self.resource_id_to_contract_address
+                .insert(resource_id, &contract_address);
+            self.contract_address_to_resource_id
+                .insert(contract_address, &resource_id);
+            self.contract_whitelist.insert(contract_address, &true);
+
+            EmitEvent::<TokenWrapperHandler>::emit_event(
+                self.env(),
+                ResourceAdded {
+                    resource_id,
+                    contract_address,
+                },
+            );
+        }
+
+        fn is_bridge_or_admin(&self) -> bool {
+            let caller = self.env().caller();
+            caller == self.bridge_address || caller == self.admin
+        }
     }
 }
@@ -9,30 +9,37 @@ mod treasury_handler {
     use ink_prelude::vec::Vec;
     use ink_storage::traits::{PackedLayout, SpreadLayout, StorageLayout};
     use ink_storage::{traits::SpreadAllocate, Mapping};
-    use protocol_ink_lib::blake::blake2b_256_4_bytes_output;
+    use protocol_ink_lib::blake::{blake2b_256_32_bytes_output, blake2b_256_4_bytes_output};
     use protocol_ink_lib::keccak::Keccak256;
-    use protocol_ink_lib::utils::{
-        element_encoder, element_encoder_for_eight_bytes, element_encoder_for_four_bytes,
-        element_encoder_for_one_byte, element_encoder_for_sixteen_bytes,
+    use protocol_ink_lib::proposals::{
+        decode_proposal, ProposalBody, ProposalHeader, RescueTokensProposal,
+        TreasurySetHandlerProposal,
     };
+    use protocol_ink_lib::utils::{typed_chain_id, ZERO_ADDRESS};
     use treasury::TreasuryRef;
 
     /// The treasury wrapper handler result type.
     pub type Result<T> = core::result::Result<T, Error>;
+    /// The maximum number of update records returned by one query
+    pub const MAX_UPDATE_RECORDS_PER_PAGE: u32 = 100;
 
     #[ink(storage)]
     #[derive(SpreadAllocate)]
     pub struct TreasuryHandler {
         /// Contract address of previously deployed Bridge.
         bridge_address: AccountId,
+        /// Account allowed to set resources while bootstrapping, zero once renounced
+        admin: AccountId,
         /// resourceID => token contract address
         resource_id_to_contract_address: Mapping<[u8; 32], AccountId>,
         /// Execution contract address => resourceID
         contract_address_to_resource_id: Mapping<AccountId, [u8; 32]>,
         /// Execution contract address => is whitelisted
         contract_whitelist: Mapping<AccountId, bool>,
-        /// (src_chain_id, nonce) -> UpdateRecord
+        /// (src_chain_id, sequence) -> UpdateRecord, the sequence counts the records of the chain
         update_records: Mapping<(u64, u64), UpdateRecord>,
+        /// chain_id -> number of update records
+        update_record_counts: Mapping<u64, u64>,
         treasury: TreasuryRef,
     }
 
@@ -43,9 +50,30 @@ mod treasury_handler {
         pub execution_chain_id: u64,
         pub nonce: u64,
         pub resource_id: [u8; 32],
+        /// the executed function signature
+        pub function_signature: [u8; 4],
+        /// the blake2b-256 hash of the proposal data
+        pub proposal_hash: [u8; 32],
+        /// the new handler of a set handler proposal, the recipient of a rescue tokens proposal
         pub update_value: [u8; 32],
     }
 
+    #[ink(event)]
+    pub struct ResourceAdded {
+        #[ink(topic)]
+        resource_id: [u8; 32],
+        #[ink(topic)]
+        contract_address: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ResourceRemoved {
+        #[ink(topic)]
+        resource_id: [u8; 32],
+        #[ink(topic)]
+        contract_address: AccountId,
+    }
+
     /// The token wrapper handler error types.
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -66,6 +94,8 @@ mod treasury_handler {
         SetHandlerError,
         /// Rescue Tokens Error
         RescueTokensError,
+        /// Proposal Decode Error
+        ProposalDecodeError,
     }
 
     impl TreasuryHandler {
@@ -95,6 +125,7 @@ mod treasury_handler {
                     });
 
                 instance.bridge_address = bridge_address;
+                instance.admin = Self::env().caller();
                 instance.treasury = treasury;
 
                 if initial_resource_ids.len() != initial_contract_addresses.len() {
@@ -105,31 +136,74 @@ mod treasury_handler {
                     let resource_id = initial_resource_ids[i];
                     let contract_address = initial_contract_addresses[i];
 
-                    instance.set_resource(resource_id, contract_address);
+                    instance.add_resource(resource_id, contract_address);
                 }
             })
         }
 
-        /// Sets the resource_ids and addresses
+        /// Sets the resource_ids and addresses, only callable by the bridge or the admin
         ///
         /// * `resource_id` -  The resource id to be mapped to.
         /// * `contract_address` -  The contract address to be mapped to
-        #[ink(message)]
+        #[ink(message, selector = 1)]
         pub fn set_resource(
             &mut self,
             resource_id: [u8; 32],
             contract_address: AccountId,
         ) -> Result<()> {
-            self.resource_id_to_contract_address
-                .insert(resource_id, &contract_address);
+            if !self.is_bridge_or_admin() {
+                return Err(Error::Unauthorized);
+            }
+
+            self.add_resource(resource_id, contract_address);
+
+            Ok(())
+        }
+
+        /// Removes the resource_id and un-whitelists its contract address, only callable by the bridge or the admin
+        ///
+        /// * `resource_id` -  The resource id to be removed
+        #[ink(message)]
+        pub fn unset_resource(&mut self, resource_id: [u8; 32]) -> Result<()> {
+            if !self.is_bridge_or_admin() {
+                return Err(Error::Unauthorized);
+            }
+
+            let contract_address = self
+                .resource_id_to_contract_address
+                .get(resource_id)
+                .ok_or(Error::InvalidResourceId)?;
+
+            self.resource_id_to_contract_address.remove(resource_id);
             self.contract_address_to_resource_id
-                .insert(contract_address.clone(), &resource_id);
-            self.contract_whitelist
-                .insert(contract_address.clone(), &true);
+                .remove(contract_address);
+            self.contract_whitelist.insert(contract_address, &false);
+
+            self.env().emit_event(ResourceRemoved {
+                resource_id,
+                contract_address,
+            });
+
+            Ok(())
+        }
+
+        /// Gives up the admin rights, resources can then only be set by the bridge
+        #[ink(message)]
+        pub fn renounce_admin(&mut self) -> Result<()> {
+            if self.env().caller() != self.admin {
+                return Err(Error::Unauthorized);
+            }
+            self.admin = ZERO_ADDRESS.into();
 
             Ok(())
         }
 
+        /// Gets admin address
+        #[ink(message)]
+        pub fn get_admin(&self) -> Result<AccountId> {
+            Ok(self.admin)
+        }
+
         /// Sets the bridge address
         ///
         /// * `bridge_address` -  The bridge address to migrate to
@@ -147,16 +221,17 @@ mod treasury_handler {
         ///
         /// * `resource_id` -  The resource id
         /// * `data` - The data to execute
-        #[ink(message, payable)]
+        #[ink(message, payable, selector = 2)]
         pub fn execute_proposal(&mut self, resource_id: [u8; 32], data: Vec<u8>) -> Result<()> {
-            // Parse the (proposal)`data`.
-            let parsed_resource_id = element_encoder(&data[0..32]);
-
             if self.env().caller() != self.bridge_address {
                 return Err(Error::Unauthorized);
             }
 
-            if parsed_resource_id != resource_id {
+            // Parse the (proposal)`data`.
+            let (header, arguments) =
+                decode_proposal(&data).map_err(|_| Error::ProposalDecodeError)?;
+
+            if header.resource_id != resource_id {
                 return Err(Error::InvalidResourceId);
             }
 
@@ -173,77 +248,134 @@ mod treasury_handler {
                 return Err(Error::UnWhitelistedContractAddress);
             }
 
-            // extract function signature
-            let function_signature = element_encoder_for_four_bytes(&data[32..36]);
-            let arguments = &data[36..];
-            self.execute_function_signature(function_signature, arguments)?;
+            let mut record =
+                self.execute_function_signature(header, arguments, anchor_address.unwrap())?;
+            record.proposal_hash = blake2b_256_32_bytes_output(&data);
+            self.store_update_record(&record);
 
             Ok(())
         }
 
         /// Executes the function signature
         ///
-        /// * `function_signature` -  The signature to be interpreted and executed on the vanchor contract
-        /// * `arguments` - The function arguments to be passed to respective functions in the vanchor contract
+        /// * `header` -  The proposal header, its function signature is interpreted and executed on the treasury contract
+        /// * `arguments` - The function arguments to be passed to respective functions in the treasury contract
+        /// * `treasury_address` - The treasury contract address
+        ///
+        /// Returns the update record of the executed proposal, without the proposal hash
         pub fn execute_function_signature(
             &mut self,
-            function_signature: [u8; 4],
+            header: ProposalHeader,
             arguments: &[u8],
-        ) -> Result<()> {
+            treasury_address: AccountId,
+        ) -> Result<UpdateRecord> {
+            let function_signature = header.function_signature;
+            let mut record = UpdateRecord {
+                treasury_address,
+                execution_chain_id: typed_chain_id(&header.resource_id),
+                nonce: header.nonce.into(),
+                resource_id: header.resource_id,
+                function_signature,
+                ..Default::default()
+            };
+
             if function_signature
                 == blake2b_256_4_bytes_output(b"Treasury::set_handler".to_vec().as_slice())
             {
-                let nonce_bytes: [u8; 4] = element_encoder_for_four_bytes(&arguments[0..4]);
-                let token_address: [u8; 32] = element_encoder(&arguments[4..36]);
-
-                let nonce = u32::from_be_bytes(nonce_bytes);
+                let proposal = TreasurySetHandlerProposal::decode(arguments)
+                    .map_err(|_| Error::ProposalDecodeError)?;
 
                 if self
                     .treasury
-                    .set_handler(token_address.into(), nonce)
+                    .set_handler(proposal.handler.into(), header.nonce)
                     .is_err()
                 {
                     return Err(Error::SetHandlerError);
                 }
+
+                record.update_value = proposal.handler;
             } else if function_signature
                 == blake2b_256_4_bytes_output(b"Treasury::rescue_tokens".to_vec().as_slice())
             {
-                let nonce_bytes: [u8; 4] = element_encoder_for_four_bytes(&arguments[0..4]);
-                let token_address: [u8; 32] = element_encoder(&arguments[4..36]);
-                let to: [u8; 32] = element_encoder(&arguments[36..68]);
-                let amount_to_rescue_bytes: [u8; 16] =
-                    element_encoder_for_sixteen_bytes(&arguments[68..84]);
-
-                let nonce = u32::from_be_bytes(nonce_bytes);
-                let amount_to_rescue = u128::from_be_bytes(amount_to_rescue_bytes);
+                let proposal = RescueTokensProposal::decode(arguments)
+                    .map_err(|_| Error::ProposalDecodeError)?;
 
                 if self
                     .treasury
                     .rescue_tokens(
-                        token_address.into(),
-                        to.into(),
-                        amount_to_rescue.into(),
-                        nonce,
+                        proposal.token_address.into(),
+                        proposal.to.into(),
+                        proposal.amount_to_rescue,
+                        header.nonce,
                     )
                     .is_err()
                 {
                     return Err(Error::RescueTokensError);
                 }
+
+                record.update_value = proposal.to;
+            } else {
+                return Err(Error::InvalidFunctionSignature);
             }
-            Ok(())
+            Ok(record)
         }
 
         /// Gets update record
         ///
         /// * `src_chain_id` -  The src_chain_id to query
-        /// * `nonce` - nonce
+        /// * `sequence` - The position of the record in the execution order of the chain
         #[ink(message)]
-        pub fn read_update_record(&self, src_chain_id: u64, nonce: u64) -> Result<UpdateRecord> {
-            if self.update_records.get((src_chain_id, nonce)).is_none() {
+        pub fn read_update_record(&self, src_chain_id: u64, sequence: u64) -> Result<UpdateRecord> {
+            if self.update_records.get((src_chain_id, sequence)).is_none() {
                 return Err(Error::UpdateRecordNotFound);
             }
 
-            Ok(self.update_records.get((src_chain_id, nonce)).unwrap())
+            Ok(self.update_records.get((src_chain_id, sequence)).unwrap())
+        }
+
+        /// Gets the latest update record of a chain
+        ///
+        /// * `src_chain_id` -  The chain id to query
+        #[ink(message)]
+        pub fn read_latest_update_record(&self, src_chain_id: u64) -> Result<UpdateRecord> {
+            let count = self.get_update_record_count(src_chain_id);
+            if count == 0 {
+                return Err(Error::UpdateRecordNotFound);
+            }
+
+            self.read_update_record(src_chain_id, count - 1)
+        }
+
+        /// Gets the update records of a chain, in execution order
+        ///
+        /// * `src_chain_id` -  The chain id to query
+        /// * `from` - The sequence of the first record
+        /// * `limit` - The maximum number of records, capped at `MAX_UPDATE_RECORDS_PER_PAGE`
+        #[ink(message)]
+        pub fn read_update_records(
+            &self,
+            src_chain_id: u64,
+            from: u64,
+            limit: u32,
+        ) -> Vec<UpdateRecord> {
+            let limit = limit.min(MAX_UPDATE_RECORDS_PER_PAGE) as u64;
+            let to = self
+                .get_update_record_count(src_chain_id)
+                .min(from.saturating_add(limit));
+
+            (from..to)
+                .filter_map(|sequence| self.read_update_record(src_chain_id, sequence).ok())
+                .collect()
+        }
+
+        /// Gets the number of update records of a chain
+        ///
+        /// * `src_chain_id` -  The chain id to query
+        #[ink(message)]
+        pub fn get_update_record_count(&self, src_chain_id: u64) -> u64 {
+            self.update_record_counts
+                .get(src_chain_id)
+                .unwrap_or_default()
         }
 
         /// Gets bridge address
@@ -335,5 +467,34 @@ mod treasury_handler {
             .concat();
             Ok(result)
         }
+
+        /// Appends an update record to the records of its chain
+        ///
+        /// * `record` - The record of an executed proposal
+        fn store_update_record(&mut self, record: &UpdateRecord) {
+            let sequence = self.get_update_record_count(record.execution_chain_id);
+            self.update_records
+                .insert((record.execution_chain_id, sequence), record);
+            self.update_record_counts
+                .insert(record.execution_chain_id, &(sequence + 1));
+        }
+
+        fn add_resource(&mut self, resource_id: [u8; 32], contract_address: AccountId) {
+            self.resource_id_to_contract_address
+                .insert(resource_id, &contract_address);
+            self.contract_address_to_resource_id
+                .insert(contract_address, &resource_id);
+            self.contract_whitelist.insert(contract_address, &true);
+
+            self.env().emit_event(ResourceAdded {
+                resource_id,
+                contract_address,
+            });
+        }
+
+        fn is_bridge_or_admin(&self) -> bool {
+            let caller = self.env().caller();
+            caller == self.bridge_address || caller == self.admin
+        }
     }
 }
@@ -3,6 +3,8 @@
 
 mod linkable_merkle_tree;
 mod merkle_tree;
+#[cfg(test)]
+mod tests;
 use ink_env::call::FromAccountId;
 use ink_storage::traits::SpreadAllocate;
 
@@ -21,23 +23,35 @@ pub mod vanchor {
     use crate::linkable_merkle_tree::{Edge, LinkableMerkleTree};
     use crate::merkle_tree::MerkleTree;
     use governed_token_wrapper::governed_token_wrapper::GovernedTokenWrapperRef;
+    use ink_env::call::FromAccountId;
+    // the token wrapper contract implements `EmitEvent` too, so events name this contract explicitly
+    use ink_lang::codegen::{EmitEvent, TraitCallBuilder};
+    use ink_lang::ToAccountId;
     use ink_prelude::string::String;
     use ink_prelude::vec::Vec;
-    use ink_storage::traits::{PackedLayout, SpreadLayout, StorageLayout};
+    use ink_primitives::KeyPtr;
+    use ink_storage::traits::{ExtKeyPtr, PackedLayout, SpreadLayout, StorageLayout};
     use ink_storage::{traits::SpreadAllocate, Mapping};
     use openbrush::contracts::psp22::extensions::metadata::*;
-    use openbrush::contracts::traits::psp22::PSP22;
+    use openbrush::contracts::traits::psp22::{PSP22Ref, PSP22};
     use openbrush::traits::Storage;
     use poseidon::poseidon::PoseidonRef;
     use protocol_ink_lib::field_ops::{ArkworksIntoFieldBn254, IntoPrimeField};
     use protocol_ink_lib::keccak::Keccak256;
-    use protocol_ink_lib::utils::element_encoder;
-    use protocol_ink_lib::vanchor_verifier::VAnchorVerifier;
+    use protocol_ink_lib::poseidon::PoseidonHasherBn254;
+    use protocol_ink_lib::utils::{element_encoder, is_account_id_zero};
     use protocol_ink_lib::zeroes::zeroes;
+    use vanchor_verifier::VAnchorVerifierRef;
 
     /// The vanchor result type.
     pub type Result<T> = core::result::Result<T, Error>;
     pub const INK_CHAIN_TYPE: [u8; 2] = [4, 0];
+    /// The default maximum length of an encrypted output
+    pub const DEFAULT_MAX_ENCRYPTED_OUTPUT_LENGTH: u32 = 1024;
+    /// The bounds of a root history size, every root costs a storage slot and a lookup when
+    /// checking proofs
+    pub const MIN_ROOT_HISTORY_SIZE: u32 = 1;
+    pub const MAX_ROOT_HISTORY_SIZE: u32 = 1024;
     pub const ERROR_MSG: &'static str =
         "requested transfer failed. this can be the case if the contract does not\
     have sufficient free funds or if the transfer would have brought the\
@@ -57,8 +71,6 @@ pub mod vanchor {
         pub merkle_tree: MerkleTree,
         /// The linkable merkle tree
         pub linkable_tree: LinkableMerkleTree,
-        /// ERC20 token wrapper address
-        pub tokenwrapper_addr: AccountId,
         /// maximum deposit amount
         pub max_deposit_amt: Balance,
         /// minimum withdrawal amount
@@ -67,34 +79,71 @@ pub mod vanchor {
         pub max_ext_amt: Balance,
         /// maximum fee
         pub max_fee: Balance,
+        /// maximum length of an encrypted output
+        pub max_encrypted_output_len: u32,
         pub handler: AccountId,
         pub proposal_nonce: u64,
 
         /// used nullifiers
         pub used_nullifiers: Mapping<[u8; 32], bool>,
 
-        pub poseidon: PoseidonRef,
-        pub verifier_2_2: Vec<u8>,
-        pub verifier_16_2: Vec<u8>,
+        pub poseidon: PoseidonBackend,
+        pub verifier: VAnchorVerifierRef,
         pub token_wrapper: GovernedTokenWrapperRef,
     }
 
     impl PSP22 for VAnchor {}
 
     #[ink(event)]
-    pub struct TransactDeposit {
+    pub struct Transaction {
         #[ink(topic)]
-        from: AccountId,
+        recipient: AccountId,
         #[ink(topic)]
-        to: AccountId,
+        relayer: AccountId,
+        ext_amount: i128,
+        fee: Balance,
+        input_nullifiers: Vec<[u8; 32]>,
+        output_commitments: Vec<[u8; 32]>,
     }
 
     #[ink(event)]
-    pub struct TransactWithdraw {
+    pub struct EdgeAddition {
         #[ink(topic)]
-        input_nullifier: [u8; 32],
+        chain_id: u64,
+        latest_leaf_index: u32,
+        merkle_root: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct EdgeUpdate {
+        #[ink(topic)]
+        chain_id: u64,
+        latest_leaf_index: u32,
+        merkle_root: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct NewCommitment {
         #[ink(topic)]
-        output_commitment: [u8; 32],
+        commitment: [u8; 32],
+        leaf_index: u32,
+        encrypted_output: Vec<u8>,
+    }
+
+    #[ink(event)]
+    pub struct NewNullifier {
+        #[ink(topic)]
+        nullifier: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct Insertion {
+        #[ink(topic)]
+        commitment: [u8; 32],
+        leaf_index: u32,
+        timestamp: Timestamp,
+        #[ink(topic)]
+        new_root: [u8; 32],
     }
 
     #[derive(Default, Debug, scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout)]
@@ -102,10 +151,51 @@ pub mod vanchor {
     pub struct ExtData {
         pub recipient: AccountId,
         pub relayer: AccountId,
-        pub ext_amount: String, // Still `String` since represents `i128` value
+        pub ext_amount: i128,
         pub fee: u128,
-        pub encrypted_output1: [u8; 32],
-        pub encrypted_output2: [u8; 32],
+        pub encrypted_output1: Vec<u8>,
+        pub encrypted_output2: Vec<u8>,
+    }
+
+    impl ExtData {
+        /// Returns the hash the transaction proof commits to as its `ext_data_hash`
+        ///
+        /// Like webb's EVM contracts this is
+        /// `uint256(keccak256(abi.encode(extData))) % FIELD_SIZE`, with `extData` the Solidity
+        /// struct `(bytes32 recipient, int256 extAmount, bytes32 relayer, uint256 fee,
+        /// bytes encryptedOutput1, bytes encryptedOutput2)`.
+        /// Account ids fill a whole 32 byte word where the EVM pads a 20 byte address. The
+        /// field element is returned as 32 little endian bytes, like every public input.
+        pub fn hash(&self) -> Result<[u8; 32]> {
+            Keccak256::hash_evm(&self.abi_encode()).map_err(|_| Error::HashError)
+        }
+
+        /// Returns the `abi.encode` of the ext data as laid out on `hash`
+        fn abi_encode(&self) -> Vec<u8> {
+            // a struct with dynamic members is encoded behind the offset of its head
+            let mut encoded = Vec::new();
+            encoded.extend_from_slice(&abi_encode_uint(32));
+
+            // the encrypted outputs follow the six head words
+            let output1_offset = 6 * 32;
+            let output2_offset =
+                output1_offset + 32 + abi_padded_length(self.encrypted_output1.len());
+            encoded.extend_from_slice(self.recipient.as_ref());
+            encoded.extend_from_slice(&abi_encode_int(self.ext_amount));
+            encoded.extend_from_slice(self.relayer.as_ref());
+            encoded.extend_from_slice(&abi_encode_uint(self.fee));
+            encoded.extend_from_slice(&abi_encode_uint(output1_offset as u128));
+            encoded.extend_from_slice(&abi_encode_uint(output2_offset as u128));
+
+            for encrypted_output in [&self.encrypted_output1, &self.encrypted_output2] {
+                encoded.extend_from_slice(&abi_encode_uint(encrypted_output.len() as u128));
+                encoded.extend_from_slice(encrypted_output);
+                // everything before the output is whole words, so this pads the output
+                encoded.resize(abi_padded_length(encoded.len()), 0);
+            }
+
+            encoded
+        }
     }
 
     #[derive(Default, Debug, scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout)]
@@ -119,6 +209,31 @@ pub mod vanchor {
         pub ext_data_hash: [u8; 32],
     }
 
+    /// Where the merkle tree nodes are hashed
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout,
+    )]
+    #[cfg_attr(feature = "std", derive(StorageLayout, scale_info::TypeInfo))]
+    pub enum PoseidonBackend {
+        /// Poseidon in Wasm with the BN254 parameters compiled in
+        Inline,
+        /// Cross-contract calls to a `Poseidon` contract
+        Contract(AccountId),
+    }
+
+    impl Default for PoseidonBackend {
+        fn default() -> Self {
+            PoseidonBackend::Inline
+        }
+    }
+
+    impl SpreadAllocate for PoseidonBackend {
+        fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+            ptr.next_for::<Self>();
+            Self::default()
+        }
+    }
+
     #[derive(Default, Debug, scale::Encode, scale::Decode, Clone, SpreadLayout, PackedLayout)]
     #[cfg_attr(feature = "std", derive(StorageLayout, scale_info::TypeInfo))]
     pub struct TokenWrapperData {
@@ -148,7 +263,7 @@ pub mod vanchor {
         VerifyError,
         /// Edge already exists
         EdgeAlreadyExists,
-        ///  Too many edges
+        /// Returned if `max_edges - 1` neighbors are already linked
         TooManyEdges,
         /// Unmatched edges
         UnmatchedEdges,
@@ -186,36 +301,67 @@ pub mod vanchor {
         UnWrappingError,
         /// Invalid Nonce
         InvalidNonce,
+        /// Invalid encrypted output length
+        InvalidEncryptedOutputLength,
+        /// Invalid latest leaf index
+        InvalidLatestLeafIndex,
+        /// Invalid root history size
+        InvalidRootHistorySize,
+        /// Native tokens sent don't match the native amount to wrap
+        InvalidNativeValue,
     }
 
     impl VAnchor {
+        /// Instantiates the anchor along with its verifier, its token wrapper and, unless it
+        /// hashes inline, its Poseidon contract
+        ///
+        /// Panics if `max_edges` is 0, if a root history size is out of bounds or if one of
+        /// the contracts fails to instantiate
         #[ink(constructor)]
         pub fn new(
             max_edges: u32,
             chain_id: u64,
             levels: u32,
+            root_history_size: u32,
+            neighbor_root_history_size: u32,
             max_deposit_amt: Balance,
             min_withdraw_amt: Balance,
             max_ext_amt: Balance,
             max_fee: Balance,
-            tokenwrapper_addr: AccountId,
             handler: AccountId,
+            verifier_governor: AccountId,
             token_wrapper_data: TokenWrapperData,
             version: u32,
-            poseidon_contract_hash: Hash,
+            poseidon_contract_hash: Option<Hash>,
+            verifier_contract_hash: Hash,
             token_wrapper_contract_hash: Hash,
         ) -> Self {
             ink_env::debug_println!("Instantiating contract");
+            // `max_edges` counts the anchor itself, proofs always carry its root
+            assert!(max_edges >= 1, "Max edges must count the anchor itself");
+            assert!(
+                is_valid_root_history_size(root_history_size)
+                    && is_valid_root_history_size(neighbor_root_history_size),
+                "Root history size is out of bounds"
+            );
+
             let salt = version.to_le_bytes();
-            let poseidon = PoseidonRef::new()
-                .endowment(0)
-                .code_hash(poseidon_contract_hash)
-                .salt_bytes(salt)
-                .instantiate()
-                .unwrap_or_else(|error| {
-                    ink_env::debug_print!("contract error in poseidon init {:?}", error);
-                    panic!("failed at instantiating the Poseidon contract: {:?}", error)
-                });
+            // without a Poseidon contract the merkle tree hashes inline
+            let poseidon = match poseidon_contract_hash {
+                Some(poseidon_contract_hash) => {
+                    let poseidon = PoseidonRef::new()
+                        .endowment(0)
+                        .code_hash(poseidon_contract_hash)
+                        .salt_bytes(salt)
+                        .instantiate()
+                        .unwrap_or_else(|error| {
+                            ink_env::debug_print!("contract error in poseidon init {:?}", error);
+                            panic!("failed at instantiating the Poseidon contract: {:?}", error)
+                        });
+                    PoseidonBackend::Contract(poseidon.to_account_id())
+                }
+                None => PoseidonBackend::Inline,
+            };
 
             let token_wrapper = GovernedTokenWrapperRef::new(
                 token_wrapper_data.name,
@@ -240,23 +386,18 @@ pub mod vanchor {
                 )
             });
 
-            let verifier_2_2 =
-                protocol_ink_lib::vanchor_verifier::VAnchorVerifier::new(max_edges, 2, 2)
-                    .unwrap_or_else(|error| {
-                        panic!(
-                            "failed at constructing verifier(2,2) for the Vanchor contract: {:?}",
-                            error
-                        )
-                    });
-
-            let verifier_16_2 =
-                protocol_ink_lib::vanchor_verifier::VAnchorVerifier::new(max_edges, 16, 2)
-                    .unwrap_or_else(|error| {
-                        panic!(
-                            "failed at constructing verifier(16,2) for the Vanchor contract: {:?}",
-                            error
-                        )
-                    });
+            // the verifier governor registers the verifying keys, the handler can't call it
+            let verifier = VAnchorVerifierRef::new(max_edges, verifier_governor)
+                .endowment(0)
+                .code_hash(verifier_contract_hash)
+                .salt_bytes(salt)
+                .instantiate()
+                .unwrap_or_else(|error| {
+                    panic!(
+                        "failed at instantiating the VAnchorVerifier contract: {:?}",
+                        error
+                    )
+                });
 
             ink_lang::utils::initialize_contract(|contract: &mut VAnchor| {
                 contract.chain_id = chain_id;
@@ -265,19 +406,20 @@ pub mod vanchor {
                 contract.min_withdraw_amt = min_withdraw_amt;
                 contract.max_deposit_amt = max_deposit_amt;
                 contract.max_fee = max_fee;
-                contract.tokenwrapper_addr = tokenwrapper_addr;
+                contract.max_encrypted_output_len = DEFAULT_MAX_ENCRYPTED_OUTPUT_LENGTH;
                 contract.handler = handler;
 
                 contract.linkable_tree.max_edges = max_edges;
+                contract.linkable_tree.root_history_size = neighbor_root_history_size;
                 contract.linkable_tree.chain_id_list = Vec::new();
 
                 contract.merkle_tree.levels = levels;
+                contract.merkle_tree.root_history_size = root_history_size;
                 contract.merkle_tree.current_root_index = 0;
                 contract.merkle_tree.next_index = 0;
 
                 contract.poseidon = poseidon;
-                contract.verifier_2_2 = verifier_2_2;
-                contract.verifier_16_2 = verifier_16_2;
+                contract.verifier = verifier;
                 contract.token_wrapper = token_wrapper;
 
                 for i in 0..levels {
@@ -315,6 +457,12 @@ pub mod vanchor {
             self.handler
         }
 
+        /// Returns the number of commitments the merkle tree can still hold.
+        #[ink(message)]
+        pub fn remaining_capacity(&self) -> u64 {
+            self.merkle_tree.remaining_capacity()
+        }
+
         #[ink(message)]
         pub fn update_vanchor_config(&mut self, max_ext_amt: u128, max_fee: u128) -> Result<()> {
             if self.creator != Self::env().caller() {
@@ -352,6 +500,161 @@ pub mod vanchor {
             Ok(())
         }
 
+        #[ink(message)]
+        pub fn configure_max_encrypted_output_length(
+            &mut self,
+            max_encrypted_output_len: u32,
+        ) -> Result<()> {
+            if self.creator != Self::env().caller() {
+                return Err(Error::Unauthorized);
+            }
+
+            self.max_encrypted_output_len = max_encrypted_output_len;
+
+            Ok(())
+        }
+
+        /// Sets the number of latest roots of the merkle tree that are accepted in proofs
+        ///
+        /// * `root_history_size` - The new root history size
+        #[ink(message)]
+        pub fn configure_root_history_size(&mut self, root_history_size: u32) -> Result<()> {
+            if self.creator != Self::env().caller() {
+                return Err(Error::Unauthorized);
+            }
+
+            if !is_valid_root_history_size(root_history_size) {
+                return Err(Error::InvalidRootHistorySize);
+            }
+
+            self.merkle_tree.set_root_history_size(root_history_size);
+
+            Ok(())
+        }
+
+        /// Sets the number of latest roots of each neighbor that are accepted in proofs
+        ///
+        /// * `root_history_size` - The new root history size
+        #[ink(message)]
+        pub fn configure_neighbor_root_history_size(
+            &mut self,
+            root_history_size: u32,
+        ) -> Result<()> {
+            if self.creator != Self::env().caller() {
+                return Err(Error::Unauthorized);
+            }
+
+            if !is_valid_root_history_size(root_history_size) {
+                return Err(Error::InvalidRootHistorySize);
+            }
+
+            self.linkable_tree.set_root_history_size(root_history_size);
+
+            Ok(())
+        }
+
+        /// Returns the `root_history_size` value of the merkle tree.
+        #[ink(message)]
+        pub fn root_history_size(&self) -> u32 {
+            self.merkle_tree.root_history_size
+        }
+
+        /// Returns the `root_history_size` value of the linkable tree.
+        #[ink(message)]
+        pub fn neighbor_root_history_size(&self) -> u32 {
+            self.linkable_tree.root_history_size
+        }
+
+        /// Returns the `max_encrypted_output_len` value.
+        #[ink(message)]
+        pub fn max_encrypted_output_length(&self) -> u32 {
+            self.max_encrypted_output_len
+        }
+
+        /// Returns the `poseidon` value, where the merkle tree nodes are hashed.
+        #[ink(message)]
+        pub fn poseidon_backend(&self) -> PoseidonBackend {
+            self.poseidon
+        }
+
+        /// Returns the address of the verifier contract.
+        #[ink(message)]
+        pub fn verifier(&self) -> AccountId {
+            self.verifier.to_account_id()
+        }
+
+        /// Returns the address of the token wrapper contract holding the pool's funds.
+        #[ink(message)]
+        pub fn token_wrapper(&self) -> AccountId {
+            self.token_wrapper.to_account_id()
+        }
+
+        /// Returns the latest root of the merkle tree.
+        #[ink(message)]
+        pub fn get_last_root(&self) -> [u8; 32] {
+            self.merkle_tree.get_last_root()
+        }
+
+        /// Returns up to `n` of the latest roots of the merkle tree, the latest root first.
+        ///
+        /// * `n` - The number of roots to return
+        #[ink(message)]
+        pub fn get_root_history(&self, n: u32) -> Vec<[u8; 32]> {
+            self.merkle_tree.get_root_history(n)
+        }
+
+        /// Returns up to `n` of the latest roots of a neighbor anchor, the latest root first.
+        ///
+        /// * `chain_id` - The chain id of the neighbor anchor
+        /// * `n` - The number of roots to return
+        #[ink(message)]
+        pub fn get_neighbor_root_history(&self, chain_id: u64, n: u32) -> Vec<[u8; 32]> {
+            self.linkable_tree.get_neighbor_root_history(chain_id, n)
+        }
+
+        /// Returns the latest neighbor roots in ascending chain id order, padded with the
+        /// empty tree root up to `max_edges - 1` roots, as expected by `transact`.
+        #[ink(message)]
+        pub fn get_latest_neighbor_roots(&self) -> Vec<[u8; 32]> {
+            let mut roots = self.linkable_tree.get_neighbor_roots();
+            roots.resize(
+                self.linkable_tree.max_edges.saturating_sub(1) as usize,
+                zeroes(self.merkle_tree.levels),
+            );
+            roots
+        }
+
+        /// Returns the edge to a neighbor anchor, if any.
+        ///
+        /// * `chain_id` - The chain id of the neighbor anchor
+        #[ink(message)]
+        pub fn get_edge(&self, chain_id: u64) -> Option<Edge> {
+            self.linkable_tree.edges.get(&chain_id)
+        }
+
+        /// Returns the `next_index` value of the merkle tree.
+        #[ink(message)]
+        pub fn get_next_index(&self) -> u32 {
+            self.merkle_tree.next_index
+        }
+
+        /// Returns whether a nullifier was already spent.
+        ///
+        /// * `nullifier` - The nullifier to check
+        #[ink(message)]
+        pub fn is_known_nullifier(&self, nullifier: [u8; 32]) -> bool {
+            self.used_nullifiers.get(&nullifier).is_some()
+        }
+
+        /// Adds or updates the edge to a neighbor anchor
+        ///
+        /// `max_edges` counts the anchor itself, so at most `max_edges - 1` neighbors can be
+        /// linked and adding another one returns `TooManyEdges`.
+        ///
+        /// * `src_chain_id` - The chain id of the neighbor anchor
+        /// * `root` - The latest root of the neighbor anchor
+        /// * `latest_leaf_index` - The latest leaf index of the neighbor anchor
+        /// * `target` - The neighbor anchor address or tree identifier
         #[ink(message)]
         pub fn update_edge(
             &mut self,
@@ -360,6 +663,11 @@ pub mod vanchor {
             latest_leaf_index: u32,
             target: [u8; 32],
         ) -> Result<()> {
+            // only current handler can execute this function
+            if self.handler != self.env().caller() {
+                return Err(Error::Unauthorized);
+            }
+
             let edge = Edge {
                 chain_id: src_chain_id,
                 root,
@@ -367,126 +675,137 @@ pub mod vanchor {
                 target,
             };
 
-            self.linkable_tree.update_edge(edge);
+            let is_new_edge = !self.linkable_tree.has_edge(src_chain_id);
+            self.linkable_tree.update_edge(edge)?;
+
+            if is_new_edge {
+                EmitEvent::<VAnchor>::emit_event(
+                    self.env(),
+                    EdgeAddition {
+                        chain_id: src_chain_id,
+                        latest_leaf_index,
+                        merkle_root: root,
+                    },
+                );
+            } else {
+                EmitEvent::<VAnchor>::emit_event(
+                    self.env(),
+                    EdgeUpdate {
+                        chain_id: src_chain_id,
+                        latest_leaf_index,
+                        merkle_root: root,
+                    },
+                );
+            }
 
             Ok(())
         }
 
-        #[ink(message)]
-        pub fn transact_deposit(
+        /// Executes a shielded transaction
+        ///
+        /// A positive `ext_amount` deposits into the pool, a negative one withdraws from it
+        /// and zero makes a pure shielded transfer.
+        ///
+        /// * `proof_data` - The transaction proof and its public inputs
+        /// * `ext_data` - The external data of the transaction
+        /// * `wrap_unwrap_token` - The token to wrap a deposit from or to unwrap a withdrawal into,
+        /// the zero address stands for the native token and `None` moves the wrapped token as is
+        ///
+        /// Native tokens can only be sent along with a deposit wrapping the native token and
+        /// must match the amount to wrap.
+        #[ink(message, payable)]
+        pub fn transact(
             &mut self,
             proof_data: ProofData,
             ext_data: ExtData,
-            recv_token_addr: AccountId,
-            recv_token_amt: Balance,
+            wrap_unwrap_token: Option<AccountId>,
         ) -> Result<()> {
-            if self.tokenwrapper_addr != recv_token_addr {
-                return Err(Error::Unauthorized);
-            }
-
-            self.validate_proof(proof_data.clone(), ext_data.clone());
-
-            let ext_data_fee: u128 = ext_data.fee.clone();
-            let ext_amt: i128 = ext_data.ext_amount.parse().expect("Invalid ext_amount");
+            let ext_amt = ext_data.ext_amount;
             let abs_ext_amt = ext_amt.unsigned_abs();
 
-            let is_withdraw = ext_amt.is_negative();
+            // native tokens sent along anything else would be stuck in the pool
+            let wraps_native = match wrap_unwrap_token {
+                Some(token_address) => ext_amt.is_positive() && is_account_id_zero(token_address),
+                None => false,
+            };
+            if !wraps_native && self.env().transferred_value() != 0 {
+                return Err(Error::InvalidNativeValue);
+            }
+
+            self.validate_proof(proof_data.clone(), ext_data.clone())?;
+            self.spend_nullifiers(&proof_data.input_nullifiers);
 
-            if is_withdraw {
-                return Err(Error::InvalidExecutionEntry);
-            } else {
+            if ext_amt.is_positive() {
                 if abs_ext_amt > self.max_deposit_amt {
                     return Err(Error::InvalidDepositAmount);
                 };
-                if abs_ext_amt != recv_token_amt {
-                    return Err(Error::InsufficientFunds);
-                };
-            }
 
-            let fee_exists = ext_data_fee != 0;
+                match wrap_unwrap_token {
+                    Some(token_address) => self.wrap_deposit(token_address, abs_ext_amt)?,
+                    None => self.receive_wrapped_token(abs_ext_amt)?,
+                }
+            } else if ext_amt.is_negative() {
+                if abs_ext_amt < self.min_withdraw_amt {
+                    return Err(Error::InvalidWithdrawAmount);
+                };
 
-            if fee_exists {
-                // PSP22 Token Transfer
-                if self
-                    .transfer_from(
-                        self.tokenwrapper_addr,
-                        ext_data.relayer.clone(),
-                        ext_data_fee,
-                        Vec::<u8>::new(),
-                    )
-                    .is_err()
-                {
-                    return Err(Error::TransferError);
+                match wrap_unwrap_token {
+                    Some(token_address) => {
+                        self.token_wrapper
+                            .unwrap_and_send_to(token_address, abs_ext_amt, ext_data.recipient)
+                            .map_err(|_| Error::UnWrappingError)?;
+                    }
+                    None => self.transfer_wrapped_token(ext_data.recipient, abs_ext_amt)?,
                 }
             }
 
-            self.execute_insertions(proof_data.clone());
+            if ext_data.fee != 0 {
+                self.transfer_wrapped_token(ext_data.relayer, ext_data.fee)?;
+            }
+
+            self.execute_insertions(&proof_data, &ext_data)?;
+            self.emit_transaction(&proof_data, &ext_data, ext_amt);
+
             Ok(())
         }
 
-        #[ink(message, payable)]
-        pub fn transact_deposit_wrap_native(
+        #[ink(message)]
+        pub fn transact_deposit(
             &mut self,
             proof_data: ProofData,
             ext_data: ExtData,
+            recv_token_addr: AccountId,
+            recv_token_amt: Balance,
         ) -> Result<()> {
-            let ext_data_fee: u128 = ext_data.fee.clone();
-            let ext_amt: i128 = ext_data.ext_amount.parse().expect("Invalid ext_amount");
-            let abs_ext_amt = ext_amt.unsigned_abs();
-
-            let amount_to_wrap = self
-                .token_wrapper
-                .get_amount_to_wrap(abs_ext_amt)
-                .map_err(|_| Error::WrappingError)?;
+            // deposits are pulled in the pool's wrapped token
+            if self.token_wrapper.to_account_id() != recv_token_addr {
+                return Err(Error::Unauthorized);
+            }
 
-            // get native token amount
-            let recv_token_amt = self.env().transferred_value();
+            if ext_data.ext_amount.is_negative() {
+                return Err(Error::InvalidExecutionEntry);
+            }
 
-            if recv_token_amt != amount_to_wrap {
+            if ext_data.ext_amount.unsigned_abs() != recv_token_amt {
                 return Err(Error::InsufficientFunds);
             };
 
-            self.validate_proof(proof_data.clone(), ext_data.clone());
+            self.transact(proof_data, ext_data, None)
+        }
 
-            let is_withdraw = ext_amt.is_negative();
-            if is_withdraw {
+        #[ink(message, payable)]
+        pub fn transact_deposit_wrap_native(
+            &mut self,
+            proof_data: ProofData,
+            ext_data: ExtData,
+        ) -> Result<()> {
+            if ext_data.ext_amount.is_negative() {
                 return Err(Error::InvalidExecutionEntry);
-            } else {
-                if abs_ext_amt > self.max_deposit_amt {
-                    return Err(Error::InvalidDepositAmount);
-                };
-
-                let zero_address = self.token_wrapper.get_zero_address();
-
-                // wrap token
-                self.token_wrapper
-                    .wrap_for_and_send_to(
-                        zero_address,
-                        self.env().caller(),
-                        0,
-                        self.env().account_id(),
-                    )
-                    .map_err(|_| Error::WrappingError)?;
-            }
-
-            let fee_exists = ext_data_fee != 0;
-            if fee_exists {
-                // PSP22 Token Transfer
-                if self
-                    .transfer_from(
-                        self.tokenwrapper_addr,
-                        ext_data.relayer.clone(),
-                        ext_data_fee,
-                        Vec::<u8>::new(),
-                    )
-                    .is_err()
-                {
-                    return Err(Error::TransferError);
-                }
             }
 
-            self.execute_insertions(proof_data.clone());
-            Ok(())
+            let zero_address = self.token_wrapper.get_zero_address();
+
+            self.transact(proof_data, ext_data, Some(zero_address))
         }
 
         #[ink(message)]
@@ -497,63 +816,25 @@ pub mod vanchor {
             recv_token_addr: AccountId,
             recv_token_amt: Balance,
         ) -> Result<()> {
-            let ext_data_fee: u128 = ext_data.fee.clone();
-            let ext_amt: i128 = ext_data.ext_amount.parse().expect("Invalid ext_amount");
-            let abs_ext_amt = ext_amt.unsigned_abs();
-
-            // Only non-"TokenWrapper" Cw20 token contract can execute this message.
-            if self.tokenwrapper_addr == self.env().account_id() {
+            // Only non-"TokenWrapper" PSP22 tokens can be wrapped.
+            if self.token_wrapper.to_account_id() == recv_token_addr {
                 return Err(Error::Unauthorized);
             }
 
+            if ext_data.ext_amount.is_negative() {
+                return Err(Error::InvalidExecutionEntry);
+            }
+
             let amount_to_wrap = self
                 .token_wrapper
-                .get_amount_to_wrap(abs_ext_amt)
+                .get_amount_to_wrap(ext_data.ext_amount.unsigned_abs())
                 .map_err(|_| Error::WrappingError)?;
 
             if recv_token_amt != amount_to_wrap {
                 return Err(Error::InsufficientFunds);
             };
 
-            self.validate_proof(proof_data.clone(), ext_data.clone());
-
-            let is_withdraw = ext_amt.is_negative();
-            if is_withdraw {
-                return Err(Error::InvalidExecutionEntry);
-            } else {
-                if abs_ext_amt > self.max_deposit_amt {
-                    return Err(Error::InvalidDepositAmount);
-                };
-
-                // wrap token
-                self.token_wrapper
-                    .wrap_for_and_send_to(
-                        self.tokenwrapper_addr,
-                        self.env().caller(),
-                        amount_to_wrap,
-                        self.env().account_id(),
-                    )
-                    .map_err(|_| Error::WrappingError)?;
-            }
-
-            let fee_exists = ext_data_fee != 0;
-            if fee_exists {
-                // PSP22 Token Transfer
-                if self
-                    .transfer_from(
-                        self.tokenwrapper_addr,
-                        ext_data.relayer.clone(),
-                        ext_data_fee,
-                        Vec::<u8>::new(),
-                    )
-                    .is_err()
-                {
-                    return Err(Error::TransferError);
-                }
-            }
-            self.execute_insertions(proof_data.clone());
-
-            Ok(())
+            self.transact(proof_data, ext_data, Some(recv_token_addr))
         }
 
         #[ink(message)]
@@ -562,93 +843,28 @@ pub mod vanchor {
             proof_data: ProofData,
             ext_data: ExtData,
         ) -> Result<()> {
-            self.validate_proof(proof_data.clone(), ext_data.clone());
-
-            let ext_data_fee: u128 = ext_data.fee.clone();
-            let ext_amt: i128 = ext_data.ext_amount.parse().expect("Invalid ext_amount");
-            let abs_ext_amt = ext_amt.unsigned_abs();
-
-            if ext_amt.is_positive() {
+            if ext_data.ext_amount.is_positive() {
                 return Err(Error::InvalidExecutionEntry);
-            } else {
-                if abs_ext_amt < self.min_withdraw_amt {
-                    return Err(Error::InvalidWithdrawAmount);
-                };
-
-                if self
-                    .transfer_from(
-                        self.tokenwrapper_addr,
-                        ext_data.relayer.clone(),
-                        ext_data_fee,
-                        Vec::<u8>::new(),
-                    )
-                    .is_err()
-                {
-                    return Err(Error::TransferError);
-                }
             }
 
-            let fee_exists = ext_data_fee != 0;
-
-            if fee_exists {
-                // PSP22 Token Transfer
-                if self
-                    .transfer_from(
-                        self.tokenwrapper_addr,
-                        ext_data.relayer.clone(),
-                        ext_data_fee,
-                        Vec::<u8>::new(),
-                    )
-                    .is_err()
-                {
-                    return Err(Error::TransferError);
-                }
-            }
-
-            Ok(())
+            self.transact(proof_data, ext_data, None)
         }
 
+        /// Withdraws and unwraps the wrapped token into `unwrap_token_addr`
+        ///
+        /// * `unwrap_token_addr` - The token to unwrap into, the zero address for the native token
         #[ink(message)]
         pub fn transact_withdraw_unwrap(
             &mut self,
             proof_data: ProofData,
             ext_data: ExtData,
+            unwrap_token_addr: AccountId,
         ) -> Result<()> {
-            self.validate_proof(proof_data.clone(), ext_data.clone());
-
-            let ext_data_fee: u128 = ext_data.fee.clone();
-            let ext_amt: i128 = ext_data.ext_amount.parse().expect("Invalid ext_amount");
-            let abs_ext_amt = ext_amt.unsigned_abs();
-
-            if ext_amt.is_positive() {
+            if ext_data.ext_amount.is_positive() {
                 return Err(Error::InvalidExecutionEntry);
-            } else {
-                if abs_ext_amt < self.min_withdraw_amt {
-                    return Err(Error::InvalidWithdrawAmount);
-                };
-
-                self.token_wrapper
-                    .unwrap_and_send_to(self.tokenwrapper_addr, abs_ext_amt, ext_data.recipient)
-                    .map_err(|_| Error::UnWrappingError)?;
-            }
-
-            let fee_exists = ext_data_fee != 0;
-            if fee_exists {
-                // PSP22 Token Transfer
-                if self
-                    .transfer_from(
-                        self.tokenwrapper_addr,
-                        ext_data.relayer.clone(),
-                        ext_data_fee,
-                        Vec::<u8>::new(),
-                    )
-                    .is_err()
-                {
-                    return Err(Error::TransferError);
-                }
             }
 
-            Ok(())
+            self.transact(proof_data, ext_data, Some(unwrap_token_addr))
         }
 
         #[ink(message, payable)]
@@ -697,20 +913,24 @@ pub mod vanchor {
 
         fn validate_proof(&mut self, proof_data: ProofData, ext_data: ExtData) -> Result<()> {
             let ext_data_fee: u128 = ext_data.fee;
-            let ext_amt: i128 = ext_data.ext_amount.parse().expect("Invalid ext_amount");
+            let ext_amt = ext_data.ext_amount;
 
             // Validation 1. Double check the number of roots.
             if self.linkable_tree.max_edges != proof_data.roots.len() as u32 {
                 return Err(Error::UnmatchedEdges);
             }
+            let (root, neighbor_roots) = proof_data
+                .roots
+                .split_first()
+                .ok_or(Error::UnmatchedEdges)?;
 
-            if !self.merkle_tree.is_known_root(proof_data.roots[0]) {
+            if !self.merkle_tree.is_known_root(*root) {
                 return Err(Error::UnknownRoot);
             }
 
             if !self
                 .linkable_tree
-                .is_valid_neighbor_roots(&proof_data.roots[1..])
+                .is_valid_neighbor_roots(neighbor_roots, zeroes(self.merkle_tree.levels))
             {
                 return Err(Error::InvalidMerkleRoots);
             }
@@ -721,23 +941,19 @@ pub mod vanchor {
                 }
             }
 
-            // Compute hash of abi encoded ext_data, reduced into field from config
+            // a full tree is caught before the proof is verified and the nullifiers are spent
+            if self.merkle_tree.remaining_capacity() < proof_data.output_commitments.len() as u64 {
+                return Err(Error::MerkleTreeIsFull);
+            }
+
+            if ext_data.encrypted_output1.len() > self.max_encrypted_output_len as usize
+                || ext_data.encrypted_output2.len() > self.max_encrypted_output_len as usize
+            {
+                return Err(Error::InvalidEncryptedOutputLength);
+            }
+
             // Ensure that the passed external data hash matches the computed one
-            let mut ext_data_args = Vec::new();
-            let recipient_bytes = element_encoder(ext_data.recipient.as_ref());
-            let relayer_bytes = element_encoder(ext_data.relayer.as_ref());
-            let fee_bytes = element_encoder(&ext_data_fee.to_le_bytes());
-            let ext_amt_bytes = element_encoder(&ext_amt.to_le_bytes());
-            ext_data_args.extend_from_slice(&recipient_bytes);
-            ext_data_args.extend_from_slice(&relayer_bytes);
-            ext_data_args.extend_from_slice(&ext_amt_bytes);
-            ext_data_args.extend_from_slice(&fee_bytes);
-            ext_data_args.extend_from_slice(&ext_data.encrypted_output1);
-            ext_data_args.extend_from_slice(&ext_data.encrypted_output2);
-
-            let computed_ext_data_hash =
-                Keccak256::hash(&ext_data_args).map_err(|_| Error::HashError)?;
-            if computed_ext_data_hash != proof_data.ext_data_hash {
+            if ext_data.hash()? != proof_data.ext_data_hash {
                 return Err(Error::InvalidExtData);
             }
 
@@ -754,7 +970,10 @@ pub mod vanchor {
             // Public amounnt can also be negative, in which
             // case it would wrap around the field, so we should check if FIELD_SIZE -
             // public_amount == proof_data.public_amount, in case of a negative ext_amount
-            let calc_public_amt = ext_amt - ext_data_fee as i128;
+            let calc_public_amt = i128::try_from(ext_data_fee)
+                .ok()
+                .and_then(|fee| ext_amt.checked_sub(fee))
+                .ok_or(Error::InvalidExtAmount)?;
             let calc_public_amt_bytes =
                 element_encoder(&ArkworksIntoFieldBn254::into_field(calc_public_amt));
             if calc_public_amt_bytes != proof_data.public_amount {
@@ -782,47 +1001,148 @@ pub mod vanchor {
                 bytes.extend_from_slice(root);
             }
 
-            let result = match (
-                proof_data.input_nullifiers.len(),
-                proof_data.output_commitments.len(),
-            ) {
-                (2, 2) => {
-                    let vanchor_verifier = VAnchorVerifier {
-                        vk_bytes: self.verifier_2_2.clone(),
-                    };
-                    vanchor_verifier.verify(bytes, proof_data.proof)
-                }
-                (16, 2) => {
-                    let vanchor_verifier = VAnchorVerifier {
-                        vk_bytes: self.verifier_16_2.clone(),
-                    };
-                    vanchor_verifier.verify(bytes, proof_data.proof)
-                }
-                _ => Ok(false),
-            };
+            let result = self
+                .verifier
+                .verify(
+                    self.linkable_tree.max_edges,
+                    proof_data.input_nullifiers.len() as u32,
+                    proof_data.output_commitments.len() as u32,
+                    bytes,
+                    proof_data.proof,
+                )
+                .map_err(|_| Error::VerifyError)?;
 
-            if !result.unwrap() {
+            if !result {
                 return Err(Error::InvalidTxProof);
             }
 
-            // Set used nullifiers to true
-            for nullifier in &proof_data.input_nullifiers {
+            Ok(())
+        }
+
+        /// Marks the input nullifiers of a verified transaction as spent
+        pub(crate) fn spend_nullifiers(&mut self, nullifiers: &[[u8; 32]]) {
+            for nullifier in nullifiers {
                 self.used_nullifiers.insert(nullifier, &true);
+                EmitEvent::<VAnchor>::emit_event(
+                    self.env(),
+                    NewNullifier {
+                        nullifier: *nullifier,
+                    },
+                );
             }
-
-            Ok(())
         }
 
-        fn execute_insertions(&mut self, proof_data: ProofData) -> Result<()> {
-            for comm in &proof_data.output_commitments {
-                self.merkle_tree.insert(self.poseidon.clone(), *comm);
+        /// Inserts the output commitments of a verified transaction into the tree
+        pub(crate) fn execute_insertions(
+            &mut self,
+            proof_data: &ProofData,
+            ext_data: &ExtData,
+        ) -> Result<()> {
+            let start_index = match self.poseidon {
+                PoseidonBackend::Inline => self
+                    .merkle_tree
+                    .insert_batch(&PoseidonHasherBn254::new(), &proof_data.output_commitments)?,
+                PoseidonBackend::Contract(poseidon) => self.merkle_tree.insert_batch(
+                    &PoseidonRef::from_account_id(poseidon),
+                    &proof_data.output_commitments,
+                )?,
+            };
+            let new_root = self.merkle_tree.get_last_root();
+
+            let encrypted_outputs = [&ext_data.encrypted_output1, &ext_data.encrypted_output2];
+            for (i, comm) in proof_data.output_commitments.iter().enumerate() {
+                let leaf_index = start_index + i as u32;
+
+                EmitEvent::<VAnchor>::emit_event(
+                    self.env(),
+                    NewCommitment {
+                        commitment: *comm,
+                        leaf_index,
+                        encrypted_output: encrypted_outputs
+                            .get(i)
+                            .map(|output| output.to_vec())
+                            .unwrap_or_default(),
+                    },
+                );
+                EmitEvent::<VAnchor>::emit_event(
+                    self.env(),
+                    Insertion {
+                        commitment: *comm,
+                        leaf_index,
+                        timestamp: self.env().block_timestamp(),
+                        new_root,
+                    },
+                );
             }
 
             Ok(())
         }
 
-        fn is_known_nullifier(&self, nullifier: [u8; 32]) -> bool {
-            self.used_nullifiers.get(&nullifier).is_some()
+        /// Wraps `amount` of `token_address` sent by the caller into the pool
+        fn wrap_deposit(&mut self, token_address: AccountId, amount: Balance) -> Result<()> {
+            let amount_to_wrap = self
+                .token_wrapper
+                .get_amount_to_wrap(amount)
+                .map_err(|_| Error::WrappingError)?;
+
+            let zero_address = self.token_wrapper.get_zero_address();
+            let (psp22_amount, native_amount) = if token_address == zero_address {
+                // native tokens come with the call and are passed on to the token wrapper
+                if self.env().transferred_value() != amount_to_wrap {
+                    return Err(Error::InvalidNativeValue);
+                }
+                (0, amount_to_wrap)
+            } else {
+                (amount_to_wrap, 0)
+            };
+
+            let caller = self.env().caller();
+            let pool = self.env().account_id();
+            self.token_wrapper
+                .call_mut()
+                .wrap_for_and_send_to(token_address, caller, psp22_amount, pool)
+                .transferred_value(native_amount)
+                .fire()
+                .map_err(|_| Error::WrappingError)?
+                .map_err(|_| Error::WrappingError)
+        }
+
+        /// Pulls `amount` of the wrapped token from the caller into the pool, the caller must
+        /// have approved the pool to spend it
+        fn receive_wrapped_token(&mut self, amount: Balance) -> Result<()> {
+            PSP22Ref::transfer_from(
+                &self.token_wrapper.to_account_id(),
+                self.env().caller(),
+                self.env().account_id(),
+                amount,
+                Vec::<u8>::new(),
+            )
+            .map_err(|_| Error::TransferError)
+        }
+
+        /// Pays `amount` of the wrapped token held by the pool to `to`
+        fn transfer_wrapped_token(&mut self, to: AccountId, amount: Balance) -> Result<()> {
+            PSP22Ref::transfer(
+                &self.token_wrapper.to_account_id(),
+                to,
+                amount,
+                Vec::<u8>::new(),
+            )
+            .map_err(|_| Error::TransferError)
+        }
+
+        fn emit_transaction(&self, proof_data: &ProofData, ext_data: &ExtData, ext_amount: i128) {
+            EmitEvent::<VAnchor>::emit_event(
+                self.env(),
+                Transaction {
+                    recipient: ext_data.recipient,
+                    relayer: ext_data.relayer,
+                    ext_amount,
+                    fee: ext_data.fee,
+                    input_nullifiers: proof_data.input_nullifiers.clone(),
+                    output_commitments: proof_data.output_commitments.clone(),
+                },
+            );
         }
 
         // Computes the combination bytes of "chain_type" and "chain_id".
@@ -838,4 +1158,31 @@ pub mod vanchor {
             u64::from_be_bytes(buf)
         }
     }
+
+    fn is_valid_root_history_size(root_history_size: u32) -> bool {
+        (MIN_ROOT_HISTORY_SIZE..=MAX_ROOT_HISTORY_SIZE).contains(&root_history_size)
+    }
+
+    /// Encodes an unsigned integer as a big endian 32 byte ABI word
+    fn abi_encode_uint(value: u128) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[16..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    /// Encodes a signed integer as a big endian two's complement 32 byte ABI word
+    fn abi_encode_int(value: i128) -> [u8; 32] {
+        let mut word = if value.is_negative() {
+            [0xffu8; 32]
+        } else {
+            [0u8; 32]
+        };
+        word[16..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    /// Returns the length of `length` bytes padded to whole ABI words
+    fn abi_padded_length(length: usize) -> usize {
+        (length + 31) / 32 * 32
+    }
 }
@@ -5,14 +5,13 @@ use ink_storage::traits::SpreadAllocate;
 use ink_storage::traits::StorageLayout;
 use ink_storage::traits::{PackedLayout, SpreadLayout};
 use ink_storage::Mapping;
+use protocol_ink_lib::root_history;
 use scale::{Decode, Encode, EncodeLike, Error, Input};
 
 pub type ChainId = u64;
 pub type Element = [u8; 32];
 pub type LatestLeafIndex = u32;
 
-pub const ROOT_HISTORY_SIZE: u32 = 100;
-
 #[derive(Default, Encode, Decode, Debug, Clone, SpreadLayout, PackedLayout)]
 #[cfg_attr(feature = "std", derive(StorageLayout, scale_info::TypeInfo))]
 pub struct Edge {
@@ -29,8 +28,11 @@ pub struct Edge {
 #[derive(Default, Debug, SpreadLayout, SpreadAllocate)]
 #[cfg_attr(feature = "std", derive(StorageLayout, scale_info::TypeInfo))]
 pub struct LinkableMerkleTree {
-    /// max edges
+    /// max edges, the number of roots in a proof: the anchor's own root and up to
+    /// `max_edges - 1` neighbor roots
     pub max_edges: u32,
+    /// the number of latest roots of each neighbor that are accepted in proofs
+    pub root_history_size: u32,
     /// the list of chains
     pub chain_id_list: ink_prelude::vec::Vec<ChainId>,
     /// edges
@@ -42,39 +44,51 @@ pub struct LinkableMerkleTree {
 }
 
 impl LinkableMerkleTree {
-    fn has_edge(&self, chain_id: ChainId) -> bool {
+    pub fn has_edge(&self, chain_id: ChainId) -> bool {
         self.edges.get(&chain_id).is_some()
     }
 
     pub fn update_edge(&mut self, edge: Edge) -> vanchor::Result<()> {
         if self.has_edge(edge.chain_id) {
-            assert!(
-                edge.latest_leaf_index
-                    < self
-                        .edges
-                        .get(&edge.chain_id)
-                        .unwrap_or_default()
-                        .latest_leaf_index
-                        + 65_536,
-                "latest leaf index should be greater than the previous one"
-            );
+            let previous_leaf_index = self
+                .edges
+                .get(&edge.chain_id)
+                .unwrap_or_default()
+                .latest_leaf_index;
+            // the leaf index can't decrease, nor jump more than 65_536 leaves at once
+            if edge.latest_leaf_index < previous_leaf_index
+                || edge.latest_leaf_index as u64 >= previous_leaf_index as u64 + 65_536
+            {
+                return Err(vanchor::Error::InvalidLatestLeafIndex);
+            }
+
             self.edges.insert(edge.chain_id, &edge);
             let curr_neighbor_root_index = self
                 .curr_neighbor_root_index
                 .get(&edge.chain_id)
                 .unwrap_or_default();
-            let neighbor_root_index = curr_neighbor_root_index + 1 % ROOT_HISTORY_SIZE;
+            let neighbor_root_index = (curr_neighbor_root_index + 1) % self.root_history_size;
             self.curr_neighbor_root_index
                 .insert(edge.chain_id, &neighbor_root_index);
             self.neighbor_roots
                 .insert((edge.chain_id, neighbor_root_index), &edge.root);
         } else {
+            // `max_edges` includes the anchor itself, so at most `max_edges - 1` neighbors
+            // can be linked, one per neighbor root of a proof
             let edge_count = self.chain_id_list.len() as u32;
-            assert!(self.max_edges >= edge_count as u32 + 1, "Edge list is full");
+            if edge_count >= self.max_edges.saturating_sub(1) {
+                return Err(vanchor::Error::TooManyEdges);
+            }
+
             self.edges.insert(edge.chain_id, &edge);
-            self.neighbor_roots.insert((edge.chain_id, 1), &edge.root);
-            self.curr_neighbor_root_index.insert(edge.chain_id, &1);
-            self.chain_id_list.push(edge.chain_id);
+            self.neighbor_roots.insert((edge.chain_id, 0), &edge.root);
+            self.curr_neighbor_root_index.insert(edge.chain_id, &0);
+            // keep the chain ids sorted, neighbor roots are matched in that order
+            let position = self
+                .chain_id_list
+                .binary_search(&edge.chain_id)
+                .unwrap_or_else(|position| position);
+            self.chain_id_list.insert(position, edge.chain_id);
         }
 
         Ok(())
@@ -106,47 +120,73 @@ impl LinkableMerkleTree {
             .collect()
     }
 
-    pub fn is_known_neighbor_root(&self, chain_id: ChainId, root: [u8; 32]) -> bool {
-        if root == [0u8; 32] {
-            return false;
-        }
+    /// Returns up to `n` of the latest roots of a neighbor, the latest root first
+    ///
+    /// * `chain_id` - The chain id of the neighbor
+    /// * `n` - The number of roots to return, capped at `root_history_size`
+    pub fn get_neighbor_root_history(&self, chain_id: ChainId, n: u32) -> Vec<[u8; 32]> {
+        root_history::latest_roots(
+            &self.neighbor_roots,
+            |i| (chain_id, i),
+            self.curr_neighbor_root_index
+                .get(&chain_id)
+                .unwrap_or_default(),
+            self.root_history_size,
+            n,
+        )
+    }
 
-        let mut i = self
-            .curr_neighbor_root_index
-            .get(&chain_id)
-            .unwrap_or_default();
-        for _ in 0..ROOT_HISTORY_SIZE {
-            if let Some(r) = self.neighbor_roots.get(&(chain_id, i)) {
-                if r == root {
-                    return true;
-                }
-
-                if i == 0 {
-                    i = ROOT_HISTORY_SIZE - 1;
-                } else {
-                    i -= 1;
-                }
-            }
+    /// Resizes the root history of every neighbor, keeping as many of the latest roots as fit
+    ///
+    /// * `root_history_size` - The new root history size, validated by the caller
+    pub fn set_root_history_size(&mut self, root_history_size: u32) {
+        for chain_id in self.chain_id_list.iter().copied() {
+            let neighbor_root_index = root_history::resize(
+                &mut self.neighbor_roots,
+                |i| (chain_id, i),
+                self.curr_neighbor_root_index
+                    .get(&chain_id)
+                    .unwrap_or_default(),
+                self.root_history_size,
+                root_history_size,
+            );
+            self.curr_neighbor_root_index
+                .insert(chain_id, &neighbor_root_index);
         }
+        self.root_history_size = root_history_size;
+    }
 
-        false
+    pub fn is_known_neighbor_root(&self, chain_id: ChainId, root: [u8; 32]) -> bool {
+        root_history::is_known_root(
+            &self.neighbor_roots,
+            |i| (chain_id, i),
+            self.curr_neighbor_root_index
+                .get(&chain_id)
+                .unwrap_or_default(),
+            self.root_history_size,
+            root,
+        )
     }
 
-    pub fn is_valid_neighbor_roots(&self, roots: &[[u8; 32]]) -> bool {
-        assert!(
-            roots.len() == self.max_edges as usize,
-            "Incorrect roots length"
-        );
-        for (i, edge) in self
-            .chain_id_list
-            .iter()
-            .map(|c_id| self.edges.get(c_id).unwrap_or_default())
-            .enumerate()
+    /// Checks the neighbor roots of a proof, `roots[i]` must be a known root of the i-th
+    /// linked chain in ascending chain id order, and the slots of unlinked edges must hold
+    /// `default_root`
+    ///
+    /// * `roots` - The neighbor roots, the anchor's own root is excluded
+    /// * `default_root` - The root of an empty tree
+    pub fn is_valid_neighbor_roots(&self, roots: &[[u8; 32]], default_root: [u8; 32]) -> bool {
+        if roots.len() != self.max_edges.saturating_sub(1) as usize
+            || roots.len() < self.chain_id_list.len()
         {
-            if !self.is_known_neighbor_root(edge.chain_id, roots[i]) {
-                return false;
-            }
+            return false;
         }
-        return true;
+
+        let (linked_roots, padding_roots) = roots.split_at(self.chain_id_list.len());
+
+        self.chain_id_list
+            .iter()
+            .zip(linked_roots)
+            .all(|(chain_id, root)| self.is_known_neighbor_root(*chain_id, *root))
+            && padding_roots.iter().all(|root| *root == default_root)
     }
 }
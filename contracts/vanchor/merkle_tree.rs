@@ -3,19 +3,20 @@ use super::*;
 use ink_storage::traits::StorageLayout;
 use ink_storage::traits::{SpreadAllocate, SpreadLayout};
 use ink_storage::Mapping;
-use poseidon::PoseidonRef;
 
 use crate::vanchor;
-use ink_prelude::vec;
+use ink_prelude::vec::Vec;
+use protocol_ink_lib::poseidon::PoseidonHasher;
+use protocol_ink_lib::root_history;
 use protocol_ink_lib::zeroes::zeroes;
 use scale::{Decode, Encode, Error, Input};
 
-pub const ROOT_HISTORY_SIZE: u32 = 100;
-
 #[derive(Default, Debug, SpreadLayout, SpreadAllocate)]
 #[cfg_attr(feature = "std", derive(StorageLayout, scale_info::TypeInfo))]
 pub struct MerkleTree {
     pub levels: u32,
+    /// The number of latest roots that are accepted in proofs
+    pub root_history_size: u32,
     pub current_root_index: u32,
     pub next_index: u32,
     pub filled_subtrees: Mapping<u32, [u8; 32]>,
@@ -23,69 +24,124 @@ pub struct MerkleTree {
 }
 
 impl MerkleTree {
-    fn hash_left_right(
-        &self,
-        hasher: PoseidonRef,
-        left: [u8; 32],
-        right: [u8; 32],
-    ) -> vanchor::Result<[u8; 32]> {
-        let inputs = vec![left, right];
-        hasher.hash(inputs).map_err(|_| vanchor::Error::HashError)
-    }
+    /// Inserts `leaves` at consecutive indices, hashing the nodes they share only once and
+    /// writing a single new root
+    ///
+    /// Returns the index of the first inserted leaf
+    ///
+    /// * `hasher` - The Poseidon hasher, inline or through the Poseidon contract
+    /// * `leaves` - The leaves to insert
+    pub fn insert_batch<H: PoseidonHasher>(
+        &mut self,
+        hasher: &H,
+        leaves: &[[u8; 32]],
+    ) -> vanchor::Result<u32> {
+        if self.remaining_capacity() < leaves.len() as u64 {
+            return Err(vanchor::Error::MerkleTreeIsFull);
+        }
 
-    pub fn insert(&mut self, hasher: PoseidonRef, leaf: [u8; 32]) -> vanchor::Result<u32> {
-        let next_index = self.next_index;
-        assert!(
-            !next_index == u32::from(2u32.pow(self.levels as u32)),
-            "Merkle tree is full"
-        );
+        let start_index = self.next_index;
+        if leaves.is_empty() {
+            return Ok(start_index);
+        }
 
-        let mut current_index = next_index;
-        let mut current_level_hash = leaf;
-        let mut left: [u8; 32];
-        let mut right: [u8; 32];
+        // the nodes of the current level covered by the batch, starting at `first_index`
+        let mut first_index = start_index;
+        let mut nodes = leaves.to_vec();
 
         for i in 0..self.levels {
-            if current_index % 2 == 0 {
-                left = current_level_hash;
-                right = zeroes(i);
-                self.filled_subtrees.insert(i, &current_level_hash);
-            } else {
-                left = self.filled_subtrees.get(&i).unwrap_or_default();
-                right = current_level_hash;
+            let last_index = first_index + nodes.len() as u32 - 1;
+
+            let mut parents = Vec::with_capacity(nodes.len() / 2 + 1);
+            for parent_index in first_index / 2..=last_index / 2 {
+                let left_index = parent_index * 2;
+                // a left sibling outside of the batch was inserted before it
+                let left = if left_index < first_index {
+                    self.filled_subtrees.get(&i).unwrap_or_default()
+                } else {
+                    nodes[(left_index - first_index) as usize]
+                };
+                let right = nodes
+                    .get((left_index + 1 - first_index) as usize)
+                    .copied()
+                    .unwrap_or_else(|| zeroes(i));
+
+                let parent = hasher
+                    .hash_left_right(left, right)
+                    .map_err(|_| vanchor::Error::HashError)?;
+                parents.push(parent);
+            }
+
+            // the last left node of the level is the sibling of the next insertions
+            let last_left_index = last_index - last_index % 2;
+            if last_left_index >= first_index {
+                self.filled_subtrees
+                    .insert(i, &nodes[(last_left_index - first_index) as usize]);
             }
 
-            current_level_hash = self.hash_left_right(hasher.clone(), left, right)?;
-            current_index = current_index / 2;
+            first_index /= 2;
+            nodes = parents;
         }
 
-        let new_root_index = (self.current_root_index + 1) % ROOT_HISTORY_SIZE;
+        let new_root_index = (self.current_root_index + 1) % self.root_history_size;
         self.current_root_index = new_root_index;
-        self.roots.insert(new_root_index, &current_level_hash);
-        self.next_index = next_index + 1;
-        Ok(next_index)
+        self.roots.insert(new_root_index, &nodes[0]);
+        self.next_index = start_index + leaves.len() as u32;
+        Ok(start_index)
     }
 
-    pub fn is_known_root(&self, root: [u8; 32]) -> bool {
-        if root == [0u8; 32] {
-            return false;
-        }
+    /// Returns the number of leaves the tree can hold
+    pub fn capacity(&self) -> u64 {
+        1u64.checked_shl(self.levels).unwrap_or(u64::MAX)
+    }
 
-        let mut i = self.current_root_index;
-        for _ in 0..ROOT_HISTORY_SIZE {
-            if let Some(r) = self.roots.get(&i) {
-                if r == root {
-                    return true;
-                }
+    /// Returns the number of leaves that can still be inserted
+    pub fn remaining_capacity(&self) -> u64 {
+        // `next_index` is a u32, so no more than u32::MAX leaves can ever be inserted
+        self.capacity()
+            .min(u32::MAX.into())
+            .saturating_sub(self.next_index.into())
+    }
 
-                if i == 0 {
-                    i = ROOT_HISTORY_SIZE - 1;
-                } else {
-                    i -= 1;
-                }
-            }
-        }
+    /// Returns the latest root of the tree
+    pub fn get_last_root(&self) -> [u8; 32] {
+        self.roots.get(self.current_root_index).unwrap_or_default()
+    }
 
-        false
+    /// Returns up to `n` of the latest roots of the tree, the latest root first
+    ///
+    /// * `n` - The number of roots to return, capped at `root_history_size`
+    pub fn get_root_history(&self, n: u32) -> Vec<[u8; 32]> {
+        root_history::latest_roots(
+            &self.roots,
+            |i| i,
+            self.current_root_index,
+            self.root_history_size,
+            n,
+        )
+    }
+
+    /// Resizes the root history, keeping as many of the latest roots as fit
+    ///
+    /// * `root_history_size` - The new root history size, validated by the caller
+    pub fn set_root_history_size(&mut self, root_history_size: u32) {
+        self.current_root_index = root_history::resize(
+            &mut self.roots,
+            |i| i,
+            self.current_root_index,
+            self.root_history_size,
+            root_history_size,
+        );
+        self.root_history_size = root_history_size;
+    }
+
+    pub fn is_known_root(&self, root: [u8; 32]) -> bool {
+        root_history::is_known_root(
+            &self.roots,
+            |i| i,
+            self.current_root_index,
+            self.root_history_size,
+            root,
+        )
     }
 }
@@ -0,0 +1,661 @@
+use crate::vanchor::{Error, ExtData, ProofData, VAnchor, MAX_ROOT_HISTORY_SIZE};
+use governed_token_wrapper::GovernedTokenWrapperRef;
+use ink_env::call::FromAccountId;
+use ink_lang as ink;
+use ink_prelude::vec;
+use protocol_ink_lib::blake::blake2b_256_4_bytes_output;
+use protocol_ink_lib::field_ops::{ArkworksIntoFieldBn254, IntoPrimeField};
+use protocol_ink_lib::poseidon::{PoseidonHasher, PoseidonHasherBn254};
+use protocol_ink_lib::proposals::{decode_proposal, AnchorUpdateProposal, ProposalBody};
+use protocol_ink_lib::utils::element_encoder;
+use protocol_ink_lib::zeroes::zeroes;
+use scale::Encode;
+
+const MAX_EDGES: u32 = 2;
+const LEVELS: u32 = 30;
+const ROOT_HISTORY_SIZE: u32 = 100;
+const CHAIN_ID: u64 = 1;
+const ROOT: [u8; 32] = [1u8; 32];
+const NEIGHBOR_CHAIN_ID: u64 = 2;
+const NEIGHBOR_ROOT: [u8; 32] = [2u8; 32];
+
+/// Sets up a vanchor knowing `ROOT` and linked to a neighbor knowing `NEIGHBOR_ROOT`
+fn setup() -> VAnchor {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+
+    let mut vanchor = ink_lang::utils::initialize_contract(|contract: &mut VAnchor| {
+        contract.chain_id = CHAIN_ID;
+        contract.creator = accounts.alice;
+        contract.handler = accounts.alice;
+        contract.token_wrapper = GovernedTokenWrapperRef::from_account_id(accounts.django);
+        contract.max_deposit_amt = 1_000;
+        contract.min_withdraw_amt = 0;
+        contract.max_ext_amt = 1_000;
+        contract.max_fee = 100;
+        contract.max_encrypted_output_len = 512;
+
+        contract.linkable_tree.max_edges = MAX_EDGES;
+        contract.linkable_tree.root_history_size = ROOT_HISTORY_SIZE;
+
+        contract.merkle_tree.levels = LEVELS;
+        contract.merkle_tree.root_history_size = ROOT_HISTORY_SIZE;
+        contract.merkle_tree.roots.insert(0, &ROOT);
+    });
+
+    assert_eq!(
+        vanchor.update_edge(NEIGHBOR_CHAIN_ID, NEIGHBOR_ROOT, 1, [0u8; 32]),
+        Ok(())
+    );
+
+    vanchor
+}
+
+fn ext_data(ext_amount: i128, fee: u128) -> ExtData {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+
+    ExtData {
+        recipient: accounts.bob,
+        relayer: accounts.charlie,
+        ext_amount,
+        fee,
+        encrypted_output1: vec![3u8; 200],
+        encrypted_output2: vec![4u8; 300],
+    }
+}
+
+/// Builds proof data consistent with `ext_data`, the proof itself is bogus
+fn proof_data(ext_data: &ExtData, num_inputs: u8) -> ProofData {
+    let public_amount = ext_data.ext_amount - ext_data.fee as i128;
+
+    ProofData {
+        proof: vec![0u8; 128],
+        public_amount: element_encoder(&ArkworksIntoFieldBn254::into_field(public_amount)),
+        roots: vec![ROOT, NEIGHBOR_ROOT],
+        input_nullifiers: (0..num_inputs).map(|i| [10 + i; 32]).collect(),
+        output_commitments: vec![[30u8; 32], [31u8; 32]],
+        ext_data_hash: ext_data.hash().unwrap(),
+    }
+}
+
+#[ink::test]
+fn transact_withdraw_fails_with_unmatched_edges() {
+    let mut vanchor = setup();
+    let ext_data = ext_data(-10, 1);
+    let mut proof_data = proof_data(&ext_data, 2);
+    proof_data.roots.push(ROOT);
+
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data, ext_data),
+        Err(Error::UnmatchedEdges)
+    );
+}
+
+#[ink::test]
+fn transact_withdraw_fails_without_roots() {
+    let mut vanchor = setup();
+    let ext_data = ext_data(-10, 1);
+    let mut proof_data = proof_data(&ext_data, 2);
+    proof_data.roots.clear();
+
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data.clone(), ext_data.clone()),
+        Err(Error::UnmatchedEdges)
+    );
+
+    // no edge count matches an empty root list, not even a zero one
+    vanchor.linkable_tree.max_edges = 0;
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data, ext_data),
+        Err(Error::UnmatchedEdges)
+    );
+}
+
+#[ink::test]
+fn transact_withdraw_fails_with_unknown_root() {
+    let mut vanchor = setup();
+    let ext_data = ext_data(-10, 1);
+    let mut proof_data = proof_data(&ext_data, 2);
+    proof_data.roots[0] = [9u8; 32];
+
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data, ext_data),
+        Err(Error::UnknownRoot)
+    );
+}
+
+#[ink::test]
+fn transact_withdraw_fails_with_unknown_neighbor_root() {
+    let mut vanchor = setup();
+    let ext_data = ext_data(-10, 1);
+    let mut proof_data = proof_data(&ext_data, 2);
+    proof_data.roots[1] = [9u8; 32];
+
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data, ext_data),
+        Err(Error::InvalidMerkleRoots)
+    );
+}
+
+#[ink::test]
+fn transact_withdraw_fails_with_reused_nullifier() {
+    let mut vanchor = setup();
+    let ext_data = ext_data(-10, 1);
+    let proof_data = proof_data(&ext_data, 2);
+    vanchor
+        .used_nullifiers
+        .insert(proof_data.input_nullifiers[1], &true);
+
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data, ext_data),
+        Err(Error::AlreadyRevealedNullfier)
+    );
+}
+
+#[ink::test]
+fn transact_withdraw_fails_when_the_tree_is_full() {
+    let hasher = PoseidonHasherBn254::new();
+    let mut vanchor = setup();
+    // a tree of one level holds two leaves
+    vanchor.merkle_tree.levels = 1;
+    let ext_data = ext_data(-10, 1);
+    let proof_data = proof_data(&ext_data, 2);
+
+    assert_eq!(vanchor.remaining_capacity(), 2);
+    assert_eq!(
+        vanchor.merkle_tree.insert_batch(&hasher, &[[20u8; 32]]),
+        Ok(0)
+    );
+    assert_eq!(vanchor.remaining_capacity(), 1);
+
+    // the two outputs of the transaction don't fit anymore
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data.clone(), ext_data.clone()),
+        Err(Error::MerkleTreeIsFull)
+    );
+
+    assert_eq!(
+        vanchor.merkle_tree.insert_batch(&hasher, &[[21u8; 32]]),
+        Ok(1)
+    );
+    assert_eq!(vanchor.remaining_capacity(), 0);
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data.clone(), ext_data),
+        Err(Error::MerkleTreeIsFull)
+    );
+    // the transaction failed before spending its nullifiers
+    assert!(!vanchor.is_known_nullifier(proof_data.input_nullifiers[0]));
+}
+
+#[ink::test]
+fn transaction_emits_an_event_per_nullifier_and_commitment() {
+    let hasher = PoseidonHasherBn254::new();
+    let mut vanchor = setup();
+    assert_eq!(
+        vanchor
+            .merkle_tree
+            .insert_batch(&hasher, &[[20u8; 32], [21u8; 32], [22u8; 32]]),
+        Ok(0)
+    );
+    let ext_data = ext_data(-10, 1);
+    let proof_data = proof_data(&ext_data, 2);
+    let previous_events = ink_env::test::recorded_events().count();
+
+    // what `transact` records once the proof is verified, around the token transfers
+    vanchor.spend_nullifiers(&proof_data.input_nullifiers);
+    assert_eq!(vanchor.execute_insertions(&proof_data, &ext_data), Ok(()));
+
+    let new_root = vanchor.get_last_root();
+    let timestamp = ink_env::block_timestamp::<ink_env::DefaultEnvironment>();
+    // the first byte is the index of the event in the contract
+    let expected_events = vec![
+        (4u8, proof_data.input_nullifiers[0].encode()),
+        (4, proof_data.input_nullifiers[1].encode()),
+        (
+            3,
+            (
+                proof_data.output_commitments[0],
+                3u32,
+                &ext_data.encrypted_output1,
+            )
+                .encode(),
+        ),
+        (
+            5,
+            (proof_data.output_commitments[0], 3u32, timestamp, new_root).encode(),
+        ),
+        (
+            3,
+            (
+                proof_data.output_commitments[1],
+                4u32,
+                &ext_data.encrypted_output2,
+            )
+                .encode(),
+        ),
+        (
+            5,
+            (proof_data.output_commitments[1], 4u32, timestamp, new_root).encode(),
+        ),
+    ];
+    let events = ink_env::test::recorded_events()
+        .skip(previous_events)
+        .map(|event| (event.data[0], event.data[1..].to_vec()))
+        .collect::<Vec<_>>();
+    assert_eq!(events, expected_events);
+
+    assert!(proof_data
+        .input_nullifiers
+        .iter()
+        .all(|nullifier| vanchor.is_known_nullifier(*nullifier)));
+    assert_eq!(vanchor.get_next_index(), 5);
+}
+
+#[ink::test]
+fn ext_data_hash_matches_known_vector() {
+    let ext_data = ExtData {
+        recipient: [1u8; 32].into(),
+        relayer: [2u8; 32].into(),
+        ext_amount: -10,
+        fee: 1,
+        encrypted_output1: vec![3u8; 4],
+        encrypted_output2: vec![4u8; 6],
+    };
+
+    // keccak256 of the 352 bytes of `abi.encode` reduced into the field, the digest was computed
+    // off-chain from the same ext data encoded as the Solidity struct documented on `hash`
+    assert_eq!(
+        ext_data.hash(),
+        Ok([
+            0x9d, 0x61, 0x5e, 0x73, 0x72, 0x5d, 0x66, 0x5f, 0x21, 0x60, 0x35, 0xc5, 0x2f, 0x16,
+            0xc8, 0x11, 0xbb, 0xfc, 0x56, 0x6f, 0x6f, 0xa5, 0xc0, 0xce, 0x7b, 0xa2, 0xab, 0xd6,
+            0x20, 0x5a, 0xd9, 0x2e,
+        ])
+    );
+}
+
+#[ink::test]
+fn transact_withdraw_fails_with_mismatched_ext_data_hash() {
+    let mut vanchor = setup();
+    let ext_data = ext_data(-10, 1);
+    let proof_data = proof_data(&ext_data, 2);
+
+    let mut tampered_ext_data = ext_data;
+    tampered_ext_data.fee = 2;
+
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data, tampered_ext_data),
+        Err(Error::InvalidExtData)
+    );
+}
+
+#[ink::test]
+fn transact_withdraw_fails_with_oversized_encrypted_output() {
+    let mut vanchor = setup();
+    let mut ext_data = ext_data(-10, 1);
+    ext_data.encrypted_output2 = vec![4u8; 513];
+    let proof_data = proof_data(&ext_data, 2);
+
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data, ext_data),
+        Err(Error::InvalidEncryptedOutputLength)
+    );
+}
+
+#[ink::test]
+fn transact_withdraw_fails_with_excessive_ext_amount() {
+    let mut vanchor = setup();
+    let mut ext_data = ext_data(-10, 1);
+    let mut proof_data = proof_data(&ext_data, 2);
+    ext_data.ext_amount = i128::MIN;
+    proof_data.ext_data_hash = ext_data.hash().unwrap();
+
+    assert_eq!(
+        vanchor.transact_withdraw(proof_data, ext_data),
+        Err(Error::InvalidExtAmount)
+    );
+}
+
+#[ink::test]
+fn transact_deposit_fails_with_unknown_root() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut vanchor = setup();
+    let ext_data = ext_data(10, 1);
+    let mut proof_data = proof_data(&ext_data, 2);
+    proof_data.roots[0] = [9u8; 32];
+
+    assert_eq!(
+        vanchor.transact_deposit(proof_data, ext_data, accounts.django, 10),
+        Err(Error::UnknownRoot)
+    );
+}
+
+#[ink::test]
+fn transact_deposit_fails_for_another_token() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut vanchor = setup();
+    let ext_data = ext_data(10, 1);
+    let proof_data = proof_data(&ext_data, 2);
+
+    // deposits are only taken in the pool's wrapped token
+    assert_eq!(vanchor.token_wrapper(), accounts.django);
+    assert_eq!(
+        vanchor.transact_deposit(proof_data, ext_data, accounts.eve, 10),
+        Err(Error::Unauthorized)
+    );
+}
+
+#[ink::test]
+fn transact_fails_with_native_value_it_does_not_wrap() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut vanchor = setup();
+    ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(10);
+
+    for (ext_amount, wrap_unwrap_token) in [
+        (-10, None),
+        (-10, Some([0u8; 32].into())),
+        (0, Some([0u8; 32].into())),
+        (10, None),
+        (10, Some(accounts.eve)),
+    ] {
+        let ext_data = ext_data(ext_amount, 1);
+        let proof_data = proof_data(&ext_data, 2);
+        assert_eq!(
+            vanchor.transact(proof_data, ext_data, wrap_unwrap_token),
+            Err(Error::InvalidNativeValue)
+        );
+    }
+
+    // a native deposit passes the check and goes on to validate the proof
+    let ext_data = ext_data(10, 1);
+    let mut proof_data = proof_data(&ext_data, 2);
+    proof_data.roots[0] = [9u8; 32];
+    assert_eq!(
+        vanchor.transact(proof_data, ext_data, Some([0u8; 32].into())),
+        Err(Error::UnknownRoot)
+    );
+}
+
+#[ink::test]
+fn update_edge_fails_for_non_handler() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut vanchor = setup();
+
+    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+    assert_eq!(
+        vanchor.update_edge(NEIGHBOR_CHAIN_ID, [9u8; 32], 2, [0u8; 32]),
+        Err(Error::Unauthorized)
+    );
+}
+
+#[ink::test]
+fn update_edge_fails_with_invalid_latest_leaf_index() {
+    let mut vanchor = setup();
+
+    assert_eq!(
+        vanchor.update_edge(NEIGHBOR_CHAIN_ID, [9u8; 32], 0, [0u8; 32]),
+        Err(Error::InvalidLatestLeafIndex)
+    );
+    assert_eq!(
+        vanchor.update_edge(NEIGHBOR_CHAIN_ID, [9u8; 32], 65_537, [0u8; 32]),
+        Err(Error::InvalidLatestLeafIndex)
+    );
+    assert_eq!(
+        vanchor.update_edge(NEIGHBOR_CHAIN_ID, [9u8; 32], 2, [0u8; 32]),
+        Ok(())
+    );
+}
+
+#[ink::test]
+fn update_edge_fails_with_too_many_edges() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut vanchor = setup();
+
+    // an edge update proposal as the bridge hands it to the anchor handler
+    let mut data = vec![0u8; 32];
+    data.extend_from_slice(&blake2b_256_4_bytes_output(b"VAnchor::update_edge"));
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(&(NEIGHBOR_CHAIN_ID + 1).to_be_bytes());
+    data.extend_from_slice(&[9u8; 32]);
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(&[0u8; 32]);
+
+    // the anchor handler decodes it and calls `update_edge` as the VAnchor handler
+    let (header, arguments) = decode_proposal(&data).unwrap();
+    assert_eq!(
+        header.function_signature,
+        blake2b_256_4_bytes_output(b"VAnchor::update_edge")
+    );
+    let proposal = AnchorUpdateProposal::decode(arguments).unwrap();
+    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+
+    // `MAX_EDGES` counts the anchor itself and `setup` linked the only neighbor already
+    assert_eq!(
+        vanchor.update_edge(
+            proposal.src_chain_id,
+            proposal.merkle_root,
+            proposal.latest_leaf_index,
+            proposal.target
+        ),
+        Err(Error::TooManyEdges)
+    );
+    assert!(!vanchor.linkable_tree.has_edge(NEIGHBOR_CHAIN_ID + 1));
+}
+
+#[ink::test]
+fn neighbor_root_history_wraps_around() {
+    let mut vanchor = setup();
+
+    for i in 0..ROOT_HISTORY_SIZE {
+        let root = [(i % 200) as u8 + 50; 32];
+        assert_eq!(
+            vanchor.update_edge(NEIGHBOR_CHAIN_ID, root, i + 2, [0u8; 32]),
+            Ok(())
+        );
+    }
+
+    // `NEIGHBOR_ROOT` was overwritten, the whole history of the last roots is still known
+    assert!(!vanchor
+        .linkable_tree
+        .is_known_neighbor_root(NEIGHBOR_CHAIN_ID, NEIGHBOR_ROOT));
+    for i in 0..ROOT_HISTORY_SIZE {
+        assert!(vanchor
+            .linkable_tree
+            .is_known_neighbor_root(NEIGHBOR_CHAIN_ID, [(i % 200) as u8 + 50; 32]));
+    }
+}
+
+#[ink::test]
+fn get_neighbor_root_history_works() {
+    let mut vanchor = setup();
+    for i in 0..3u8 {
+        assert_eq!(
+            vanchor.update_edge(NEIGHBOR_CHAIN_ID, [i + 5; 32], i as u32 + 2, [0u8; 32]),
+            Ok(())
+        );
+    }
+
+    assert_eq!(
+        vanchor.get_neighbor_root_history(NEIGHBOR_CHAIN_ID, 2),
+        vec![[7u8; 32], [6u8; 32]]
+    );
+    assert_eq!(
+        vanchor.get_neighbor_root_history(NEIGHBOR_CHAIN_ID, 10),
+        vec![[7u8; 32], [6u8; 32], [5u8; 32], NEIGHBOR_ROOT]
+    );
+    assert!(vanchor
+        .get_neighbor_root_history(NEIGHBOR_CHAIN_ID + 1, 10)
+        .is_empty());
+
+    // the history wraps around once more roots than `root_history_size` are linked
+    assert_eq!(vanchor.configure_neighbor_root_history_size(2), Ok(()));
+    assert_eq!(
+        vanchor.get_neighbor_root_history(NEIGHBOR_CHAIN_ID, 10),
+        vec![[7u8; 32], [6u8; 32]]
+    );
+    assert_eq!(
+        vanchor.update_edge(NEIGHBOR_CHAIN_ID, [8u8; 32], 5, [0u8; 32]),
+        Ok(())
+    );
+    assert_eq!(
+        vanchor.get_neighbor_root_history(NEIGHBOR_CHAIN_ID, 10),
+        vec![[8u8; 32], [7u8; 32]]
+    );
+}
+
+#[ink::test]
+fn neighbor_roots_are_matched_by_sorted_chain_id() {
+    let mut vanchor = setup();
+    vanchor.linkable_tree.max_edges = 4;
+    let default_root = zeroes(LEVELS);
+
+    assert_eq!(
+        vanchor.update_edge(NEIGHBOR_CHAIN_ID - 1, [9u8; 32], 1, [0u8; 32]),
+        Ok(())
+    );
+
+    assert!(vanchor
+        .linkable_tree
+        .is_valid_neighbor_roots(&[[9u8; 32], NEIGHBOR_ROOT, default_root], default_root));
+    assert!(!vanchor
+        .linkable_tree
+        .is_valid_neighbor_roots(&[NEIGHBOR_ROOT, [9u8; 32], default_root], default_root));
+    assert!(!vanchor
+        .linkable_tree
+        .is_valid_neighbor_roots(&[[9u8; 32], NEIGHBOR_ROOT, [0u8; 32]], default_root));
+}
+
+#[ink::test]
+fn query_messages_work() {
+    let mut vanchor = setup();
+    vanchor.merkle_tree.roots.insert(1, &[5u8; 32]);
+    vanchor.merkle_tree.current_root_index = 1;
+    vanchor.used_nullifiers.insert([10u8; 32], &true);
+
+    assert_eq!(vanchor.get_last_root(), [5u8; 32]);
+    assert_eq!(vanchor.get_root_history(1), vec![[5u8; 32]]);
+    assert_eq!(vanchor.get_root_history(10), vec![[5u8; 32], ROOT]);
+    assert_eq!(vanchor.get_latest_neighbor_roots(), vec![NEIGHBOR_ROOT]);
+    assert_eq!(
+        vanchor.get_edge(NEIGHBOR_CHAIN_ID).map(|edge| edge.root),
+        Some(NEIGHBOR_ROOT)
+    );
+    assert!(vanchor.get_edge(NEIGHBOR_CHAIN_ID + 1).is_none());
+    assert!(vanchor.is_known_nullifier([10u8; 32]));
+    assert!(!vanchor.is_known_nullifier([11u8; 32]));
+
+    vanchor.linkable_tree.max_edges = 3;
+    assert_eq!(
+        vanchor.get_latest_neighbor_roots(),
+        vec![NEIGHBOR_ROOT, zeroes(LEVELS)]
+    );
+}
+
+#[ink::test]
+fn configure_root_history_size_works() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut vanchor = setup();
+    for i in 1..5u8 {
+        vanchor.merkle_tree.roots.insert(i as u32, &[i + 4; 32]);
+    }
+    vanchor.merkle_tree.current_root_index = 4;
+
+    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+    assert_eq!(
+        vanchor.configure_root_history_size(3),
+        Err(Error::Unauthorized)
+    );
+
+    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+    assert_eq!(
+        vanchor.configure_root_history_size(0),
+        Err(Error::InvalidRootHistorySize)
+    );
+    assert_eq!(
+        vanchor.configure_neighbor_root_history_size(MAX_ROOT_HISTORY_SIZE + 1),
+        Err(Error::InvalidRootHistorySize)
+    );
+
+    // only the latest roots are kept
+    assert_eq!(vanchor.configure_root_history_size(3), Ok(()));
+    assert_eq!(vanchor.root_history_size(), 3);
+    assert_eq!(
+        vanchor.get_root_history(10),
+        vec![[8u8; 32], [7u8; 32], [6u8; 32]]
+    );
+    assert!(!vanchor.merkle_tree.is_known_root(ROOT));
+
+    assert_eq!(vanchor.configure_neighbor_root_history_size(1), Ok(()));
+    assert_eq!(
+        vanchor.update_edge(NEIGHBOR_CHAIN_ID, [9u8; 32], 2, [0u8; 32]),
+        Ok(())
+    );
+    assert!(!vanchor
+        .linkable_tree
+        .is_known_neighbor_root(NEIGHBOR_CHAIN_ID, NEIGHBOR_ROOT));
+    assert!(vanchor
+        .linkable_tree
+        .is_known_neighbor_root(NEIGHBOR_CHAIN_ID, [9u8; 32]));
+}
+
+/// Returns the root after each leaf, inserting the leaves one by one into a tree of `LEVELS`
+fn incremental_roots(hasher: &PoseidonHasherBn254, leaves: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    let mut filled_subtrees: Vec<[u8; 32]> = (0..LEVELS).map(zeroes).collect();
+    let mut roots = Vec::new();
+    for (index, leaf) in leaves.iter().enumerate() {
+        let mut current_index = index;
+        let mut current_level_hash = *leaf;
+        for i in 0..LEVELS {
+            let (left, right) = if current_index % 2 == 0 {
+                filled_subtrees[i as usize] = current_level_hash;
+                (current_level_hash, zeroes(i))
+            } else {
+                (filled_subtrees[i as usize], current_level_hash)
+            };
+            current_level_hash = hasher.hash_left_right(left, right).unwrap();
+            current_index /= 2;
+        }
+        roots.push(current_level_hash);
+    }
+    roots
+}
+
+#[ink::test]
+fn insert_batch_matches_incremental_insertions() {
+    let hasher = PoseidonHasherBn254::new();
+    let empty_vanchor = || {
+        ink_lang::utils::initialize_contract(|contract: &mut VAnchor| {
+            contract.merkle_tree.levels = LEVELS;
+            contract.merkle_tree.root_history_size = ROOT_HISTORY_SIZE;
+        })
+    };
+    let leaves = [zeroes(0), zeroes(0), [1u8; 32], [2u8; 32], [3u8; 32]];
+    let roots = incremental_roots(&hasher, &leaves);
+    // a tree of empty leaves hashes to the precomputed empty tree root
+    assert_eq!(roots[1], zeroes(LEVELS));
+
+    let mut sequential = empty_vanchor();
+    for (i, leaf) in leaves.iter().enumerate() {
+        assert_eq!(
+            sequential.merkle_tree.insert_batch(&hasher, &[*leaf]),
+            Ok(i as u32)
+        );
+        assert_eq!(sequential.get_last_root(), roots[i]);
+    }
+
+    // batches starting at an even and at an odd index
+    for split in [2, 3] {
+        let mut batched = empty_vanchor();
+        assert_eq!(
+            batched.merkle_tree.insert_batch(&hasher, &leaves[..split]),
+            Ok(0)
+        );
+        assert_eq!(batched.get_last_root(), roots[split - 1]);
+        assert_eq!(
+            batched.merkle_tree.insert_batch(&hasher, &leaves[split..]),
+            Ok(split as u32)
+        );
+        assert_eq!(batched.get_last_root(), roots[leaves.len() - 1]);
+        assert_eq!(batched.get_next_index(), leaves.len() as u32);
+        assert_eq!(batched.get_root_history(ROOT_HISTORY_SIZE).len(), 2);
+    }
+}
@@ -0,0 +1,368 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(test)]
+mod tests;
+
+use ink_env::call::FromAccountId;
+use ink_env::Environment;
+use ink_lang as ink;
+use ink_primitives::KeyPtr;
+#[cfg(feature = "std")]
+use ink_storage::traits::StorageLayout;
+use ink_storage::traits::{ExtKeyPtr, PackedLayout, SpreadAllocate, SpreadLayout};
+use protocol_ink_lib::verify_proof::VerifyProof;
+
+pub use self::vanchor_verifier::{VAnchorVerifier, VAnchorVerifierRef};
+
+impl SpreadAllocate for VAnchorVerifierRef {
+    fn allocate_spread(_ptr: &mut ink_primitives::KeyPtr) -> Self {
+        FromAccountId::from_account_id([0; 32].into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum CustomEnvironment {}
+
+impl Environment for CustomEnvironment {
+    const MAX_EVENT_TOPICS: usize = <ink_env::DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <ink_env::DefaultEnvironment as Environment>::AccountId;
+    type Balance = <ink_env::DefaultEnvironment as Environment>::Balance;
+    type Hash = <ink_env::DefaultEnvironment as Environment>::Hash;
+    type BlockNumber = <ink_env::DefaultEnvironment as Environment>::BlockNumber;
+    type Timestamp = <ink_env::DefaultEnvironment as Environment>::Timestamp;
+
+    type ChainExtension = VerifyProof;
+}
+
+/// Where proofs are verified
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout,
+)]
+#[cfg_attr(feature = "std", derive(StorageLayout, scale_info::TypeInfo))]
+pub enum VerificationBackend {
+    /// Groth16 verification in Wasm through arkworks
+    Wasm,
+    /// Native verification through the `VerifyProof` chain extension for the circuit whose
+    /// verifying key the runtime verifier holds, falling back to `Wasm` if the chain
+    /// extension fails, proofs of every other circuit are verified in `Wasm`
+    ChainExtension { max_edges: u32, ins: u32, outs: u32 },
+}
+
+impl VerificationBackend {
+    /// Returns the backend a verifier for anchors with `max_edges` edges starts with, with
+    /// the `chain-extension` feature the runtime verifier is expected to hold the verifying
+    /// key of their 2-2 transaction circuit
+    #[cfg(feature = "chain-extension")]
+    pub fn default_for(max_edges: u32) -> Self {
+        VerificationBackend::ChainExtension {
+            max_edges,
+            ins: 2,
+            outs: 2,
+        }
+    }
+
+    /// Returns the backend a verifier for anchors with `max_edges` edges starts with
+    #[cfg(not(feature = "chain-extension"))]
+    pub fn default_for(_max_edges: u32) -> Self {
+        VerificationBackend::Wasm
+    }
+}
+
+impl SpreadAllocate for VerificationBackend {
+    fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+        ptr.next_for::<Self>();
+        VerificationBackend::Wasm
+    }
+}
+
+mod verifier {
+    use ark_crypto_primitives::{Error, SNARK};
+    use ark_ec::PairingEngine;
+    use ark_groth16::{Groth16, Proof, VerifyingKey};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::marker::PhantomData;
+    use arkworks_native_gadgets::to_field_elements;
+    use ink_prelude::vec::Vec;
+
+    pub struct ArkworksVerifierGroth16<E: PairingEngine>(PhantomData<E>);
+
+    impl<E: PairingEngine> ArkworksVerifierGroth16<E> {
+        /// Checks a serialized verifying key and re-serializes it, so that it can be
+        /// loaded on every verification without repeating the curve and subgroup checks,
+        /// returns the preprocessed key with the number of public inputs it takes
+        pub fn preprocess_vk(vk_bytes: &[u8]) -> Result<(Vec<u8>, usize), Error> {
+            let vk = VerifyingKey::<E>::deserialize(vk_bytes)?;
+            let mut preprocessed_vk_bytes = Vec::new();
+            vk.serialize_unchecked(&mut preprocessed_vk_bytes)?;
+            // the first element of `gamma_abc_g1` belongs to the constant one input
+            let num_public_inputs = vk.gamma_abc_g1.len().saturating_sub(1);
+            Ok((preprocessed_vk_bytes, num_public_inputs))
+        }
+
+        pub fn verify(
+            public_inp_bytes: &[u8],
+            proof_bytes: &[u8],
+            preprocessed_vk_bytes: &[u8],
+        ) -> Result<bool, Error> {
+            let public_input_field_elts = to_field_elements::<E::Fr>(public_inp_bytes)?;
+            let vk = VerifyingKey::<E>::deserialize_unchecked(preprocessed_vk_bytes)?;
+            let proof = Proof::<E>::deserialize(proof_bytes)?;
+            let res = Groth16::<E>::verify(&vk, &public_input_field_elts, &proof)?;
+            Ok(res)
+        }
+    }
+
+    use ark_bn254::Bn254;
+    pub type ArkworksVerifierBn254 = ArkworksVerifierGroth16<Bn254>;
+}
+
+/// Returns the number of public inputs of a transaction circuit: the public amount, the
+/// ext data hash, one nullifier per input, one commitment per output, the chain id type
+/// and one root per edge
+fn public_input_count(max_edges: u32, ins: u32, outs: u32) -> u64 {
+    1 + 1 + ins as u64 + outs as u64 + 1 + max_edges as u64
+}
+
+#[ink::contract(env = crate::CustomEnvironment)]
+pub mod vanchor_verifier {
+    use crate::verifier::ArkworksVerifierBn254;
+    use crate::VerificationBackend;
+    use ink_prelude::vec::Vec;
+    use ink_storage::{traits::SpreadAllocate, Mapping};
+
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct VAnchorVerifier {
+        /// The account allowed to register verifying keys
+        governor: AccountId,
+        /// The number of edges of the anchors using this verifier
+        max_edges: u32,
+        /// (max_edges, ins, outs) -> preprocessed verifying key
+        verifying_keys: Mapping<(u32, u32, u32), Vec<u8>>,
+        /// Where proofs are verified
+        verification_backend: VerificationBackend,
+    }
+
+    #[ink(event)]
+    pub struct VerifyingKeyRegistered {
+        max_edges: u32,
+        ins: u32,
+        outs: u32,
+    }
+
+    #[ink(event)]
+    pub struct VerificationBackendSet {
+        verification_backend: VerificationBackend,
+    }
+
+    #[ink(event)]
+    pub struct GovernorSet {
+        #[ink(topic)]
+        governor: AccountId,
+    }
+
+    /// The verifier error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Unauthorized
+        Unauthorized,
+        /// Returned if the verifying key can't be deserialized
+        InvalidVerifyingKey,
+        /// Returned if the verifying key doesn't take the public inputs of the circuit
+        InvalidPublicInputCount,
+        /// Returned if no verifying key is registered for the circuit
+        VerifyingKeyNotFound,
+        /// Returned if error verifying
+        VerifierError,
+    }
+
+    /// The verifier result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl VAnchorVerifier {
+        /// Instantiates the verifier with the built-in verifying keys for `max_edges`, if any
+        ///
+        /// * `max_edges` - The number of edges of the anchors using this verifier
+        /// * `governor` - The account allowed to register verifying keys
+        #[ink(constructor)]
+        pub fn new(max_edges: u32, governor: AccountId) -> Self {
+            ink_lang::utils::initialize_contract(|contract: &mut VAnchorVerifier| {
+                contract.governor = governor;
+                contract.max_edges = max_edges;
+                contract.verification_backend = VerificationBackend::default_for(max_edges);
+
+                for (ins, outs) in [(2, 2), (16, 2)] {
+                    let vk_bytes = match protocol_ink_lib::vanchor_verifier::VAnchorVerifier::new(
+                        max_edges, ins, outs,
+                    ) {
+                        Ok(vk_bytes) => vk_bytes,
+                        Err(_) => continue,
+                    };
+
+                    contract
+                        .store_verifying_key(max_edges, ins, outs, &vk_bytes)
+                        .unwrap_or_else(|error| {
+                            panic!(
+                                "failed at preprocessing the built-in verifying key ({}, {}, {}): {:?}",
+                                max_edges, ins, outs, error
+                            )
+                        });
+                }
+            })
+        }
+
+        /// Registers the verifying key of a transaction circuit, replacing any previous one
+        ///
+        /// * `max_edges` - The number of edges of the circuit
+        /// * `ins` - The number of inputs of the circuit
+        /// * `outs` - The number of outputs of the circuit
+        /// * `vk_bytes` - The serialized verifying key
+        #[ink(message)]
+        pub fn register_verifying_key(
+            &mut self,
+            max_edges: u32,
+            ins: u32,
+            outs: u32,
+            vk_bytes: Vec<u8>,
+        ) -> Result<()> {
+            if self.governor != self.env().caller() {
+                return Err(Error::Unauthorized);
+            }
+
+            self.store_verifying_key(max_edges, ins, outs, &vk_bytes)?;
+
+            self.env().emit_event(VerifyingKeyRegistered {
+                max_edges,
+                ins,
+                outs,
+            });
+
+            Ok(())
+        }
+
+        /// Sets where proofs are verified
+        ///
+        /// * `verification_backend` - The new verification backend
+        #[ink(message)]
+        pub fn set_verification_backend(
+            &mut self,
+            verification_backend: VerificationBackend,
+        ) -> Result<()> {
+            if self.governor != self.env().caller() {
+                return Err(Error::Unauthorized);
+            }
+
+            self.verification_backend = verification_backend;
+
+            self.env().emit_event(VerificationBackendSet {
+                verification_backend,
+            });
+
+            Ok(())
+        }
+
+        /// Sets the account allowed to register verifying keys
+        ///
+        /// * `governor` - The new governor
+        #[ink(message)]
+        pub fn set_governor(&mut self, governor: AccountId) -> Result<()> {
+            if self.governor != self.env().caller() {
+                return Err(Error::Unauthorized);
+            }
+
+            self.governor = governor;
+
+            self.env().emit_event(GovernorSet { governor });
+
+            Ok(())
+        }
+
+        /// Verifies a transaction proof
+        ///
+        /// * `max_edges` - The number of edges of the anchor
+        /// * `ins` - The number of inputs of the transaction
+        /// * `outs` - The number of outputs of the transaction
+        /// * `public_inp_bytes` - The public inputs of the proof
+        /// * `proof_bytes` - The proof
+        #[ink(message)]
+        pub fn verify(
+            &self,
+            max_edges: u32,
+            ins: u32,
+            outs: u32,
+            public_inp_bytes: Vec<u8>,
+            proof_bytes: Vec<u8>,
+        ) -> Result<bool> {
+            let vk_bytes = self
+                .verifying_keys
+                .get((max_edges, ins, outs))
+                .ok_or(Error::VerifyingKeyNotFound)?;
+
+            // the runtime verifier only holds the verifying key of one circuit
+            if self.verification_backend
+                == (VerificationBackend::ChainExtension {
+                    max_edges,
+                    ins,
+                    outs,
+                })
+            {
+                let input = (public_inp_bytes.clone(), proof_bytes.clone());
+                if let Ok(result) = self.env().extension().verify_proof(input) {
+                    return Ok(result);
+                }
+                // the chain extension failed, the proof is verified in wasm instead
+            }
+
+            ArkworksVerifierBn254::verify(&public_inp_bytes, &proof_bytes, &vk_bytes)
+                .map_err(|_| Error::VerifierError)
+        }
+
+        /// Returns whether a verifying key is registered for a transaction circuit
+        #[ink(message)]
+        pub fn has_verifying_key(&self, max_edges: u32, ins: u32, outs: u32) -> bool {
+            self.verifying_keys.get((max_edges, ins, outs)).is_some()
+        }
+
+        /// Returns the `verification_backend` value.
+        #[ink(message)]
+        pub fn verification_backend(&self) -> VerificationBackend {
+            self.verification_backend
+        }
+
+        /// Returns the `max_edges` value.
+        #[ink(message)]
+        pub fn max_edges(&self) -> u32 {
+            self.max_edges
+        }
+
+        /// Returns the `governor` value.
+        #[ink(message)]
+        pub fn governor(&self) -> AccountId {
+            self.governor
+        }
+
+        fn store_verifying_key(
+            &mut self,
+            max_edges: u32,
+            ins: u32,
+            outs: u32,
+            vk_bytes: &[u8],
+        ) -> Result<()> {
+            let (preprocessed_vk_bytes, num_public_inputs) =
+                ArkworksVerifierBn254::preprocess_vk(vk_bytes)
+                    .map_err(|_| Error::InvalidVerifyingKey)?;
+
+            if num_public_inputs as u64 != crate::public_input_count(max_edges, ins, outs) {
+                return Err(Error::InvalidPublicInputCount);
+            }
+
+            self.verifying_keys
+                .insert((max_edges, ins, outs), &preprocessed_vk_bytes);
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::vanchor_verifier::{Error, VAnchorVerifier};
+use crate::verifier::ArkworksVerifierBn254;
+use crate::VerificationBackend;
+use ink_lang as ink;
+use ink_prelude::vec;
+use ink_prelude::vec::Vec;
+use scale::{Decode, Encode};
+
+/// A Groth16 verifying key of a circuit binding the six public inputs of a 1-1 transaction
+/// with one edge
+const VERIFYING_KEY: &str = "6d7e5a330ed4a0b17fca702b865c59fae6fdc35b2a8c08f1d110eab8656607843854f0bb0a7d8bf501494b0bdfb1cee99f77f545b1a13fb3722a10ff18ea6217ed30963a635ff270e367437dfa084030d19e4803b49396230b934c8503652a2a3efb2ee65261a393f81e0213d5228437172131daf2b70ee328156091c51f1b2268951244cd491cdc91d6f514f7ae7a4470f6cefb4dcab0cd3413d3fb102baa2920d0ec0071468685f77f80ef8f3954d3ad8546636823e55d452c1c3aeb711607b85e68b80d52fcad0a09938ca1105401bf803461a299b9ca2b4b0f558baa121f070000000000000072f8497a68319fd122c548cfcf3a2a2a80628a01d406ebf7518fa83390546701741ce5b35e4f135d069a251fc82461fa27dd94a9855ea9e23d78693f07564409b7be6696e105ac6908611982e865baf31b181c8221463713354ef4f97c1f8b965ebe31d2ce8a410e480300add064d965237a3e3cb8dfacf1b7d43bfce97b3c93785dc5d1b4283d75a636c80ab47e4f9c5dc5c568dae8342f53a1f8e90046e1251e84bde685126bd8b3e4bf3aaa9ed888bf941bdede1bb9d9721c0df69616c29d59cebf2f5bde86e8ef4699271f5627cd0626300a5ad50a891244ac6f4b44dc84";
+/// The public inputs 1 to 6 of `PROOF`
+const PUBLIC_INPUTS: &str = "010000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000300000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000005000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000";
+/// A proof of `PUBLIC_INPUTS` against `VERIFYING_KEY`
+const PROOF: &str = "dc9c6f97d6bfd309a916658a92eae694470ca9c8d523e61c2cb2895b69155b803a5a16cccc21c4f513d57844a86f2a825355d9811d5e47f1f0cdb2f3e80169274cef04aa6759c4092a41fc0cc732b8b99005226acb803983eac641934d0262a5f9d18983dd58be05bcedc4f3ecdfaf521374e33da273b1e04533eed9277a35a8";
+/// A Groth16 verifying key of a circuit binding the seven public inputs of a 2-1
+/// transaction with one edge
+const VERIFYING_KEY_2_1: &str = "6d7e5a330ed4a0b17fca702b865c59fae6fdc35b2a8c08f1d110eab8656607843854f0bb0a7d8bf501494b0bdfb1cee99f77f545b1a13fb3722a10ff18ea6217ed30963a635ff270e367437dfa084030d19e4803b49396230b934c8503652a2a3efb2ee65261a393f81e0213d5228437172131daf2b70ee328156091c51f1b2268951244cd491cdc91d6f514f7ae7a4470f6cefb4dcab0cd3413d3fb102baa2920d0ec0071468685f77f80ef8f3954d3ad8546636823e55d452c1c3aeb711607b85e68b80d52fcad0a09938ca1105401bf803461a299b9ca2b4b0f558baa121f08000000000000004dba2fe5db6f2650eec2c8ba8b6d817ffba85990dfb5a53e99f371ff7aab969f288197b750cc70b746463fb6e3f33e946e4316f6aedc340f127d7a46bb64d894e4224fb532c8f3843a5b379777ab2864dc350e6875c4da3a2e7601d31f7c2c1052f0d231e5a0c62ac083085c072c64a6eceaf7a8cb8cd4305f19aac0fe03802c4830de18f335590d42bb85287665b83a12b8cb358bd106bd90b2bcb16821fe1bd6d5e44f22c9c22b439e345e80d4f70f35072114a44583dc6244cf3eeb52619d988996757c52083560c45d0401230d1da1b2b3321eadd3692bdba24bdeaa7398de065eef970c92e57a5c88d22ce3f4ea78e244d11332899e823385f3e718a718";
+/// The public inputs 1 to 7 of `PROOF_2_1`
+const PUBLIC_INPUTS_2_1: &str = "0100000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000003000000000000000000000000000000000000000000000000000000000000000400000000000000000000000000000000000000000000000000000000000000050000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000700000000000000000000000000000000000000000000000000000000000000";
+/// A proof of `PUBLIC_INPUTS_2_1` against `VERIFYING_KEY_2_1`
+const PROOF_2_1: &str = "3a76c6a9144577884736cc42df1ee54e1abb720f2d191375cb1d8d8b662a082a072735556c6c849cf232267e0b4521ecc7ceb92432bae561a06e41b8e440d815c0e670603857365a7246db48bde5af81b733c73e06a94c1540af90723f18ac037c130fdb4857ef02d0d58aad2319c2154d7212aa3e99b12f95bd5d8cf228f19f";
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Mocks the `VerifyProof` chain extension of a runtime verifier holding `vk_bytes`
+struct MockVerifyProof {
+    status_code: u32,
+    vk_bytes: Vec<u8>,
+}
+
+impl ink_env::test::ChainExtension for MockVerifyProof {
+    fn func_id(&self) -> u32 {
+        1101
+    }
+
+    fn call(&mut self, mut input: &[u8], output: &mut Vec<u8>) -> u32 {
+        // the off-chain engine hands the encoded input over as a byte vector
+        let input = Vec::<u8>::decode(&mut input).unwrap();
+        let (public_inp_bytes, proof_bytes) =
+            <(Vec<u8>, Vec<u8>)>::decode(&mut &input[..]).unwrap();
+        let (vk_bytes, _) = ArkworksVerifierBn254::preprocess_vk(&self.vk_bytes).unwrap();
+        ArkworksVerifierBn254::verify(&public_inp_bytes, &proof_bytes, &vk_bytes)
+            .unwrap_or(false)
+            .encode_to(output);
+        self.status_code
+    }
+}
+
+/// Test that the built-in verifying keys are registered
+#[ink::test]
+fn new_registers_built_in_verifying_keys() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+
+    let verifier = VAnchorVerifier::new(2, accounts.alice);
+    assert_eq!(verifier.max_edges(), 2);
+    assert_eq!(verifier.governor(), accounts.alice);
+    assert!(verifier.has_verifying_key(2, 2, 2));
+    assert!(verifier.has_verifying_key(2, 16, 2));
+    assert!(!verifier.has_verifying_key(2, 4, 2));
+
+    let verifier = VAnchorVerifier::new(8, accounts.alice);
+    assert!(!verifier.has_verifying_key(8, 2, 2));
+}
+
+/// Test that only the governor can register verifying keys fitting the circuit
+#[ink::test]
+fn register_verifying_key_works() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut verifier = VAnchorVerifier::new(1, accounts.alice);
+
+    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+    assert_eq!(
+        verifier.register_verifying_key(1, 1, 1, from_hex(VERIFYING_KEY)),
+        Err(Error::Unauthorized)
+    );
+
+    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+    assert_eq!(
+        verifier.register_verifying_key(1, 1, 1, vec![1u8; 8]),
+        Err(Error::InvalidVerifyingKey)
+    );
+    // the key takes six public inputs, a 2-1 circuit with one edge has seven
+    assert_eq!(
+        verifier.register_verifying_key(1, 2, 1, from_hex(VERIFYING_KEY)),
+        Err(Error::InvalidPublicInputCount)
+    );
+    assert_eq!(
+        verifier.register_verifying_key(2, 1, 1, from_hex(VERIFYING_KEY)),
+        Err(Error::InvalidPublicInputCount)
+    );
+    assert!(!verifier.has_verifying_key(1, 2, 1));
+
+    assert_eq!(
+        verifier.register_verifying_key(1, 1, 1, from_hex(VERIFYING_KEY)),
+        Ok(())
+    );
+    assert!(verifier.has_verifying_key(1, 1, 1));
+    assert!(!verifier.has_verifying_key(2, 1, 1));
+}
+
+/// Test that verifying without a registered verifying key fails
+#[ink::test]
+fn verify_fails_without_verifying_key() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let verifier = VAnchorVerifier::new(2, accounts.alice);
+
+    assert_eq!(
+        verifier.verify(2, 4, 2, vec![0u8; 32], vec![0u8; 128]),
+        Err(Error::VerifyingKeyNotFound)
+    );
+    assert_eq!(
+        verifier.verify(2, 2, 2, vec![0u8; 32], vec![0u8; 128]),
+        Err(Error::VerifierError)
+    );
+}
+
+/// Test that only the governor can set the verification backend
+#[ink::test]
+fn set_verification_backend_works() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut verifier = VAnchorVerifier::new(2, accounts.alice);
+    assert_eq!(
+        verifier.verification_backend(),
+        VerificationBackend::default_for(2)
+    );
+
+    let backend = VerificationBackend::ChainExtension {
+        max_edges: 2,
+        ins: 2,
+        outs: 2,
+    };
+    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.bob);
+    assert_eq!(
+        verifier.set_verification_backend(backend),
+        Err(Error::Unauthorized)
+    );
+
+    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.alice);
+    assert_eq!(verifier.set_verification_backend(backend), Ok(()));
+    assert_eq!(verifier.verification_backend(), backend);
+}
+
+/// Test that only proofs of the circuit the runtime holds the key of go through the chain
+/// extension
+#[ink::test]
+fn verify_through_chain_extension_works() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut verifier = VAnchorVerifier::new(1, accounts.alice);
+    assert_eq!(
+        verifier.register_verifying_key(1, 1, 1, from_hex(VERIFYING_KEY)),
+        Ok(())
+    );
+    assert_eq!(
+        verifier.register_verifying_key(1, 2, 1, from_hex(VERIFYING_KEY_2_1)),
+        Ok(())
+    );
+    assert_eq!(
+        verifier.set_verification_backend(VerificationBackend::ChainExtension {
+            max_edges: 1,
+            ins: 1,
+            outs: 1,
+        }),
+        Ok(())
+    );
+
+    let mut wrong_public_inputs = from_hex(PUBLIC_INPUTS);
+    wrong_public_inputs[0] += 1;
+
+    ink_env::test::register_chain_extension(MockVerifyProof {
+        status_code: 0,
+        vk_bytes: from_hex(VERIFYING_KEY),
+    });
+    assert_eq!(
+        verifier.verify(1, 1, 1, from_hex(PUBLIC_INPUTS), from_hex(PROOF)),
+        Ok(true)
+    );
+    assert_eq!(
+        verifier.verify(1, 1, 1, wrong_public_inputs, from_hex(PROOF)),
+        Ok(false)
+    );
+    // the runtime key would reject the 2-1 proof, it is verified in wasm with its own key
+    assert_eq!(
+        verifier.verify(1, 2, 1, from_hex(PUBLIC_INPUTS_2_1), from_hex(PROOF_2_1)),
+        Ok(true)
+    );
+
+    // the answer for the 1-1 circuit comes from the runtime key
+    ink_env::test::register_chain_extension(MockVerifyProof {
+        status_code: 0,
+        vk_bytes: from_hex(VERIFYING_KEY_2_1),
+    });
+    assert_eq!(
+        verifier.verify(1, 1, 1, from_hex(PUBLIC_INPUTS), from_hex(PROOF)),
+        Ok(false)
+    );
+}
+
+/// Test that a failing chain extension falls back to the wasm verifier
+#[ink::test]
+fn verify_falls_back_to_wasm_when_chain_extension_fails() {
+    let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+    let mut verifier = VAnchorVerifier::new(1, accounts.alice);
+    assert_eq!(
+        verifier.register_verifying_key(1, 1, 1, from_hex(VERIFYING_KEY)),
+        Ok(())
+    );
+    assert_eq!(
+        verifier.set_verification_backend(VerificationBackend::ChainExtension {
+            max_edges: 1,
+            ins: 1,
+            outs: 1,
+        }),
+        Ok(())
+    );
+
+    let mut wrong_public_inputs = from_hex(PUBLIC_INPUTS);
+    wrong_public_inputs[0] += 1;
+
+    // a documented failure and an unknown status code both fall back
+    for status_code in [1, 7] {
+        ink_env::test::register_chain_extension(MockVerifyProof {
+            status_code,
+            vk_bytes: from_hex(VERIFYING_KEY),
+        });
+
+        assert_eq!(
+            verifier.verify(1, 1, 1, from_hex(PUBLIC_INPUTS), from_hex(PROOF)),
+            Ok(true)
+        );
+        assert_eq!(
+            verifier.verify(1, 1, 1, wrong_public_inputs.clone(), from_hex(PROOF)),
+            Ok(false)
+        );
+        assert_eq!(
+            verifier.verify(1, 1, 1, vec![0u8; 32], vec![0u8; 128]),
+            Err(Error::VerifierError)
+        );
+    }
+}
@@ -13,6 +13,12 @@ mod hasher {
             let field_res = F::from_le_bytes_mod_order(&res);
             field_res.into_repr().to_bytes_le()
         }
+
+        pub fn hash_be(input: &[u8]) -> Vec<u8> {
+            let res = keccak_256(input);
+            let field_res = F::from_be_bytes_mod_order(&res);
+            field_res.into_repr().to_bytes_le()
+        }
     }
 
     use ark_bn254::Fr as Bn254;
@@ -39,6 +45,14 @@ pub mod keccak {
             Ok(out)
         }
 
+        /// Hashes like the EVM contracts' `uint256(keccak256(inputs)) % FIELD_SIZE`, the digest
+        /// is read big endian and the field element is returned as little endian bytes
+        pub fn hash_evm(inputs: &[u8]) -> Result<[u8; 32], Error> {
+            let res = Keccak256Bn254::hash_be(inputs);
+            let out: [u8; 32] = res.try_into().map_err(|_| Error::HashError)?;
+            Ok(out)
+        }
+
         pub fn hash_with_four_bytes_output(inputs: &[u8]) -> Result<[u8; 4], Error> {
             let res = Keccak256Bn254::hash(inputs, &[]);
             let out: [u8; 4] = res.try_into().map_err(|_| Error::HashError)?;
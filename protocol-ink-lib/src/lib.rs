@@ -3,6 +3,13 @@
 pub mod blake;
 pub mod field_ops;
 pub mod keccak;
+pub mod poseidon;
+mod poseidon_bn254_x5_3;
+pub mod proposals;
+pub mod root_history;
+#[cfg(test)]
+mod tests;
 pub mod utils;
 pub mod vanchor_verifier;
+pub mod verify_proof;
 pub mod zeroes;
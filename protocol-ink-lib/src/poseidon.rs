@@ -0,0 +1,79 @@
+use crate::poseidon_bn254_x5_3::{FULL_ROUNDS, MDS_MATRIX, PARTIAL_ROUNDS, ROUND_KEYS};
+use ark_bn254::Fr as Bn254Fr;
+use ark_ff::{BigInteger, PrimeField};
+use arkworks_native_gadgets::poseidon::{
+    sbox::PoseidonSbox, FieldHasher, Poseidon, PoseidonParameters,
+};
+use arkworks_native_gadgets::to_field_elements;
+
+/// The width of the Poseidon permutation, two inputs and the capacity element
+pub const WIDTH: u8 = 3;
+/// The exponent of the Poseidon S-box
+pub const EXPONENTIATION: i8 = 5;
+
+/// The Poseidon hashing error types.
+#[derive(Debug, PartialEq, Eq)]
+pub enum HashError {
+    /// Returned if an input isn't a canonical field element
+    InvalidInput,
+    /// Returned if there is an error hashing
+    HashError,
+}
+
+/// Hashes two merkle tree nodes into their parent
+pub trait PoseidonHasher {
+    fn hash_left_right(&self, left: [u8; 32], right: [u8; 32]) -> Result<[u8; 32], HashError>;
+}
+
+/// Poseidon over BN254 with the width 3, exponent 5 parameters compiled in, the parameters
+/// are copied from constants, nothing is parsed when building a hasher
+pub struct PoseidonHasherBn254 {
+    poseidon: Poseidon<Bn254Fr>,
+}
+
+impl PoseidonHasherBn254 {
+    pub fn new() -> Self {
+        Self {
+            poseidon: Poseidon::new(poseidon_params_bn254()),
+        }
+    }
+}
+
+/// Returns the BN254 width 3, exponent 5 Poseidon parameters from their compiled in constants
+pub fn poseidon_params_bn254() -> PoseidonParameters<Bn254Fr> {
+    PoseidonParameters {
+        round_keys: ROUND_KEYS.to_vec(),
+        mds_matrix: MDS_MATRIX.iter().map(|row| row.to_vec()).collect(),
+        full_rounds: FULL_ROUNDS,
+        partial_rounds: PARTIAL_ROUNDS,
+        width: WIDTH,
+        sbox: PoseidonSbox(EXPONENTIATION),
+    }
+}
+
+impl Default for PoseidonHasherBn254 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoseidonHasher for PoseidonHasherBn254 {
+    fn hash_left_right(&self, left: [u8; 32], right: [u8; 32]) -> Result<[u8; 32], HashError> {
+        let mut inputs = [0u8; 64];
+        inputs[..32].copy_from_slice(&left);
+        inputs[32..].copy_from_slice(&right);
+        let field_inputs =
+            to_field_elements::<Bn254Fr>(&inputs).map_err(|_| HashError::InvalidInput)?;
+
+        let output = self
+            .poseidon
+            .hash(&field_inputs)
+            .map_err(|_| HashError::HashError)?;
+
+        output
+            .into_repr()
+            .to_bytes_le()
+            .try_into()
+            .map_err(|_| HashError::HashError)
+    }
+}
@@ -0,0 +1,243 @@
+pub const RESOURCE_ID_LENGTH: usize = 32;
+pub const FUNCTION_SIGNATURE_LENGTH: usize = 4;
+pub const NONCE_LENGTH: usize = 4;
+/// The length of a proposal header: resource_id(32) + function_signature(4) + nonce(4)
+pub const PROPOSAL_HEADER_LENGTH: usize =
+    RESOURCE_ID_LENGTH + FUNCTION_SIGNATURE_LENGTH + NONCE_LENGTH;
+
+/// The proposal decoding error types.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data is shorter than a proposal header
+    InvalidHeaderLength,
+    /// The body length doesn't match the proposal
+    InvalidBodyLength,
+    /// The data ends before the value being read
+    UnexpectedEnd,
+}
+
+/// The header every handler proposal starts with, laid out as the webb-proposals
+/// `ProposalHeader`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProposalHeader {
+    pub resource_id: [u8; 32],
+    pub function_signature: [u8; 4],
+    pub nonce: u32,
+}
+
+/// Splits proposal `data` into its header and its body
+///
+/// * `data` - The proposal data
+pub fn decode_proposal(data: &[u8]) -> Result<(ProposalHeader, &[u8]), DecodeError> {
+    if data.len() < PROPOSAL_HEADER_LENGTH {
+        return Err(DecodeError::InvalidHeaderLength);
+    }
+
+    let mut reader = BytesReader::new(data);
+    let header = ProposalHeader {
+        resource_id: reader.read()?,
+        function_signature: reader.read()?,
+        nonce: reader.read_u32()?,
+    };
+
+    Ok((header, reader.remaining()))
+}
+
+/// Reads big endian values from a byte slice
+pub struct BytesReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> BytesReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Reads the next `N` bytes, fails without consuming anything if fewer are left
+    pub fn read<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        if self.bytes.len() < N {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+
+        let (head, tail) = self.bytes.split_at(N);
+        self.bytes = tail;
+        let mut output = [0u8; N];
+        output.copy_from_slice(head);
+        Ok(output)
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, DecodeError> {
+        self.read().map(u16::from_be_bytes)
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        self.read().map(u32::from_be_bytes)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, DecodeError> {
+        self.read().map(u64::from_be_bytes)
+    }
+
+    pub fn read_u128(&mut self) -> Result<u128, DecodeError> {
+        self.read().map(u128::from_be_bytes)
+    }
+
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+/// A proposal body following the `ProposalHeader`
+pub trait ProposalBody: Sized {
+    /// The length of the encoded body
+    const LENGTH: usize;
+
+    /// Reads the body from the next `LENGTH` bytes of `reader`
+    fn read(reader: &mut BytesReader) -> Result<Self, DecodeError>;
+
+    fn decode(body: &[u8]) -> Result<Self, DecodeError> {
+        if body.len() != Self::LENGTH {
+            return Err(DecodeError::InvalidBodyLength);
+        }
+
+        Self::read(&mut BytesReader::new(body))
+    }
+}
+
+/// `VAnchor::set_handler` proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorSetHandlerProposal {
+    pub handler: [u8; 32],
+}
+
+impl ProposalBody for AnchorSetHandlerProposal {
+    const LENGTH: usize = 32;
+
+    fn read(reader: &mut BytesReader) -> Result<Self, DecodeError> {
+        Ok(Self {
+            handler: reader.read()?,
+        })
+    }
+}
+
+/// `VAnchor::update_edge` proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorUpdateProposal {
+    pub src_chain_id: u64,
+    pub merkle_root: [u8; 32],
+    pub latest_leaf_index: u32,
+    pub target: [u8; 32],
+}
+
+impl ProposalBody for AnchorUpdateProposal {
+    const LENGTH: usize = 8 + 32 + 4 + 32;
+
+    fn read(reader: &mut BytesReader) -> Result<Self, DecodeError> {
+        Ok(Self {
+            src_chain_id: reader.read_u64()?,
+            merkle_root: reader.read()?,
+            latest_leaf_index: reader.read_u32()?,
+            target: reader.read()?,
+        })
+    }
+}
+
+/// `VAnchor::configure_max_deposit_limit` and `VAnchor::configure_min_withdrawal_limit` proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnchorLimitProposal {
+    pub amount: u128,
+}
+
+impl ProposalBody for AnchorLimitProposal {
+    const LENGTH: usize = 16;
+
+    fn read(reader: &mut BytesReader) -> Result<Self, DecodeError> {
+        Ok(Self {
+            amount: reader.read_u128()?,
+        })
+    }
+}
+
+/// `GovernedTokenWrapper::set_fee` proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetFeeProposal {
+    pub fee: u16,
+}
+
+impl ProposalBody for SetFeeProposal {
+    const LENGTH: usize = 2;
+
+    fn read(reader: &mut BytesReader) -> Result<Self, DecodeError> {
+        Ok(Self {
+            fee: reader.read_u16()?,
+        })
+    }
+}
+
+/// `GovernedTokenWrapper::add_token_address` and `GovernedTokenWrapper::remove_token_address` proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAddressProposal {
+    pub token_address: [u8; 32],
+}
+
+impl ProposalBody for TokenAddressProposal {
+    const LENGTH: usize = 32;
+
+    fn read(reader: &mut BytesReader) -> Result<Self, DecodeError> {
+        Ok(Self {
+            token_address: reader.read()?,
+        })
+    }
+}
+
+/// `GovernedTokenWrapper::set_fee_recipient` proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeRecipientProposal {
+    pub fee_recipient: [u8; 32],
+}
+
+impl ProposalBody for FeeRecipientProposal {
+    const LENGTH: usize = 32;
+
+    fn read(reader: &mut BytesReader) -> Result<Self, DecodeError> {
+        Ok(Self {
+            fee_recipient: reader.read()?,
+        })
+    }
+}
+
+/// `Treasury::set_handler` proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreasurySetHandlerProposal {
+    pub handler: [u8; 32],
+}
+
+impl ProposalBody for TreasurySetHandlerProposal {
+    const LENGTH: usize = 32;
+
+    fn read(reader: &mut BytesReader) -> Result<Self, DecodeError> {
+        Ok(Self {
+            handler: reader.read()?,
+        })
+    }
+}
+
+/// `Treasury::rescue_tokens` proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RescueTokensProposal {
+    pub token_address: [u8; 32],
+    pub to: [u8; 32],
+    pub amount_to_rescue: u128,
+}
+
+impl ProposalBody for RescueTokensProposal {
+    const LENGTH: usize = 32 + 32 + 16;
+
+    fn read(reader: &mut BytesReader) -> Result<Self, DecodeError> {
+        Ok(Self {
+            token_address: reader.read()?,
+            to: reader.read()?,
+            amount_to_rescue: reader.read_u128()?,
+        })
+    }
+}
@@ -0,0 +1,111 @@
+//! The ring buffer of the latest merkle tree roots, shared by the VAnchor and Mixer trees
+//! and the VAnchor neighbor roots
+//!
+//! The buffer lives in a `Mapping`, `key` maps each slot of the buffer to its key there,
+//! so several buffers can share one `Mapping`
+use ink_prelude::vec::Vec;
+use ink_storage::Mapping;
+use scale::Encode;
+
+/// Returns up to `n` of the latest roots, the latest root first
+///
+/// * `roots` - The ring buffer of roots
+/// * `key` - Maps a slot of the ring buffer to its key in `roots`
+/// * `current_root_index` - The index of the latest root
+/// * `root_history_size` - The size of the ring buffer
+/// * `n` - The number of roots to return, capped at `root_history_size`
+pub fn latest_roots<K: Encode>(
+    roots: &Mapping<K, [u8; 32]>,
+    key: impl Fn(u32) -> K,
+    current_root_index: u32,
+    root_history_size: u32,
+    n: u32,
+) -> Vec<[u8; 32]> {
+    let mut latest_roots = Vec::new();
+    let mut i = current_root_index;
+    // the roots are stored contiguously, so the history ends at the first empty slot
+    for _ in 0..n.min(root_history_size) {
+        match roots.get(&key(i)) {
+            Some(root) => latest_roots.push(root),
+            None => break,
+        }
+
+        if i == 0 {
+            i = root_history_size - 1;
+        } else {
+            i -= 1;
+        }
+    }
+
+    latest_roots
+}
+
+/// Returns whether `root` is one of the roots in the history
+///
+/// * `roots` - The ring buffer of roots
+/// * `key` - Maps a slot of the ring buffer to its key in `roots`
+/// * `current_root_index` - The index of the latest root
+/// * `root_history_size` - The size of the ring buffer
+/// * `root` - The root to look for, the zero root is never known
+pub fn is_known_root<K: Encode>(
+    roots: &Mapping<K, [u8; 32]>,
+    key: impl Fn(u32) -> K,
+    current_root_index: u32,
+    root_history_size: u32,
+    root: [u8; 32],
+) -> bool {
+    if root == [0u8; 32] {
+        return false;
+    }
+
+    let mut i = current_root_index;
+    for _ in 0..root_history_size {
+        match roots.get(&key(i)) {
+            Some(r) if r == root => return true,
+            Some(_) => {}
+            None => break,
+        }
+
+        if i == 0 {
+            i = root_history_size - 1;
+        } else {
+            i -= 1;
+        }
+    }
+
+    false
+}
+
+/// Resizes the ring buffer, keeping as many of the latest roots as fit
+///
+/// Returns the new index of the latest root
+///
+/// * `roots` - The ring buffer of roots
+/// * `key` - Maps a slot of the ring buffer to its key in `roots`
+/// * `current_root_index` - The index of the latest root
+/// * `root_history_size` - The current size of the ring buffer
+/// * `new_root_history_size` - The new size of the ring buffer, validated by the caller
+pub fn resize<K: Encode>(
+    roots: &mut Mapping<K, [u8; 32]>,
+    key: impl Fn(u32) -> K,
+    current_root_index: u32,
+    root_history_size: u32,
+    new_root_history_size: u32,
+) -> u32 {
+    let kept_roots = latest_roots(
+        roots,
+        &key,
+        current_root_index,
+        root_history_size,
+        new_root_history_size,
+    );
+    for i in 0..root_history_size {
+        roots.remove(&key(i));
+    }
+
+    // the latest root ends up at the returned index, the oldest one at index 0
+    for (i, root) in kept_roots.iter().rev().enumerate() {
+        roots.insert(&key(i as u32), root);
+    }
+    kept_roots.len().saturating_sub(1) as u32
+}
@@ -0,0 +1,215 @@
+use crate::poseidon::{poseidon_params_bn254, PoseidonHasher, PoseidonHasherBn254};
+use crate::proposals::*;
+use crate::utils::typed_chain_id;
+use crate::zeroes::zeroes;
+use core::fmt::Debug;
+use ink_prelude::vec::Vec;
+
+/// Builds a body from big endian encoded fields
+fn body(fields: &[&[u8]]) -> Vec<u8> {
+    fields.concat()
+}
+
+/// Checks that a body one byte too short or too long is rejected
+fn assert_rejects_wrong_lengths<T: ProposalBody + Debug + PartialEq>() {
+    let bytes = ink_prelude::vec![1u8; T::LENGTH + 1];
+
+    assert_eq!(T::decode(&[]), Err(DecodeError::InvalidBodyLength));
+    assert_eq!(
+        T::decode(&bytes[..T::LENGTH - 1]),
+        Err(DecodeError::InvalidBodyLength)
+    );
+    assert_eq!(T::decode(&bytes), Err(DecodeError::InvalidBodyLength));
+    assert!(T::decode(&bytes[..T::LENGTH]).is_ok());
+}
+
+#[test]
+fn decode_proposal_splits_header_and_body() {
+    let mut data = Vec::new();
+    data.extend_from_slice(&[7u8; RESOURCE_ID_LENGTH]);
+    data.extend_from_slice(&[0, 0, 0, 2]);
+    data.extend_from_slice(&0x01020304u32.to_be_bytes());
+    data.extend_from_slice(&[1, 2, 3]);
+
+    let (header, body) = decode_proposal(&data).unwrap();
+    assert_eq!(
+        header,
+        ProposalHeader {
+            resource_id: [7u8; 32],
+            function_signature: [0, 0, 0, 2],
+            nonce: 0x01020304,
+        }
+    );
+    assert_eq!(body, &[1, 2, 3]);
+
+    // a header without body is a valid proposal
+    let (header, body) = decode_proposal(&data[..PROPOSAL_HEADER_LENGTH]).unwrap();
+    assert_eq!(header.nonce, 0x01020304);
+    assert!(body.is_empty());
+}
+
+#[test]
+fn decode_proposal_rejects_truncated_header() {
+    let data = [0u8; PROPOSAL_HEADER_LENGTH];
+
+    assert_eq!(decode_proposal(&[]), Err(DecodeError::InvalidHeaderLength));
+    assert_eq!(
+        decode_proposal(&data[..PROPOSAL_HEADER_LENGTH - 1]),
+        Err(DecodeError::InvalidHeaderLength)
+    );
+}
+
+#[test]
+fn bytes_reader_reads_big_endian_values() {
+    let data = body(&[
+        &[9, 8],
+        &0x0102u16.to_be_bytes(),
+        &0x01020304u32.to_be_bytes(),
+        &0x0102030405060708u64.to_be_bytes(),
+        &u128::MAX.to_be_bytes(),
+        &[5],
+    ]);
+    let mut reader = BytesReader::new(&data);
+
+    assert_eq!(reader.read::<2>(), Ok([9, 8]));
+    assert_eq!(reader.read_u16(), Ok(0x0102));
+    assert_eq!(reader.read_u32(), Ok(0x01020304));
+    assert_eq!(reader.read_u64(), Ok(0x0102030405060708));
+    assert_eq!(reader.read_u128(), Ok(u128::MAX));
+    assert_eq!(reader.remaining(), &[5]);
+    assert_eq!(reader.read::<1>(), Ok([5]));
+    assert!(reader.remaining().is_empty());
+}
+
+#[test]
+fn bytes_reader_fails_past_the_end() {
+    let data = [1u8, 2, 3];
+    let mut reader = BytesReader::new(&data);
+
+    assert_eq!(reader.read_u32(), Err(DecodeError::UnexpectedEnd));
+    assert_eq!(reader.read_u64(), Err(DecodeError::UnexpectedEnd));
+    assert_eq!(reader.read_u128(), Err(DecodeError::UnexpectedEnd));
+    // a failed read consumes nothing
+    assert_eq!(reader.remaining(), &[1, 2, 3]);
+    assert_eq!(reader.read_u16(), Ok(0x0102));
+    assert_eq!(reader.read_u16(), Err(DecodeError::UnexpectedEnd));
+    assert_eq!(reader.read::<1>(), Ok([3]));
+    assert_eq!(reader.read::<1>(), Err(DecodeError::UnexpectedEnd));
+}
+
+#[test]
+fn anchor_proposals_decode() {
+    let data = body(&[&[4u8; 32]]);
+    assert_eq!(
+        AnchorSetHandlerProposal::decode(&data),
+        Ok(AnchorSetHandlerProposal { handler: [4u8; 32] })
+    );
+
+    let data = body(&[
+        &5u64.to_be_bytes(),
+        &[6u8; 32],
+        &7u32.to_be_bytes(),
+        &[8u8; 32],
+    ]);
+    assert_eq!(
+        AnchorUpdateProposal::decode(&data),
+        Ok(AnchorUpdateProposal {
+            src_chain_id: 5,
+            merkle_root: [6u8; 32],
+            latest_leaf_index: 7,
+            target: [8u8; 32],
+        })
+    );
+
+    let data = body(&[&1_000_000u128.to_be_bytes()]);
+    assert_eq!(
+        AnchorLimitProposal::decode(&data),
+        Ok(AnchorLimitProposal { amount: 1_000_000 })
+    );
+
+    assert_rejects_wrong_lengths::<AnchorSetHandlerProposal>();
+    assert_rejects_wrong_lengths::<AnchorUpdateProposal>();
+    assert_rejects_wrong_lengths::<AnchorLimitProposal>();
+}
+
+#[test]
+fn token_wrapper_proposals_decode() {
+    let data = body(&[&100u16.to_be_bytes()]);
+    assert_eq!(
+        SetFeeProposal::decode(&data),
+        Ok(SetFeeProposal { fee: 100 })
+    );
+
+    let data = body(&[&[3u8; 32]]);
+    assert_eq!(
+        TokenAddressProposal::decode(&data),
+        Ok(TokenAddressProposal {
+            token_address: [3u8; 32],
+        })
+    );
+    assert_eq!(
+        FeeRecipientProposal::decode(&data),
+        Ok(FeeRecipientProposal {
+            fee_recipient: [3u8; 32],
+        })
+    );
+
+    assert_rejects_wrong_lengths::<SetFeeProposal>();
+    assert_rejects_wrong_lengths::<TokenAddressProposal>();
+    assert_rejects_wrong_lengths::<FeeRecipientProposal>();
+}
+
+#[test]
+fn treasury_proposals_decode() {
+    let data = body(&[&[5u8; 32]]);
+    assert_eq!(
+        TreasurySetHandlerProposal::decode(&data),
+        Ok(TreasurySetHandlerProposal { handler: [5u8; 32] })
+    );
+
+    let data = body(&[&[7u8; 32], &[8u8; 32], &500u128.to_be_bytes()]);
+    assert_eq!(
+        RescueTokensProposal::decode(&data),
+        Ok(RescueTokensProposal {
+            token_address: [7u8; 32],
+            to: [8u8; 32],
+            amount_to_rescue: 500,
+        })
+    );
+
+    assert_rejects_wrong_lengths::<TreasurySetHandlerProposal>();
+    assert_rejects_wrong_lengths::<RescueTokensProposal>();
+}
+
+#[test]
+fn typed_chain_id_reads_the_last_six_bytes() {
+    let mut resource_id = [9u8; 32];
+    resource_id[26..].copy_from_slice(&[0x06, 0x00, 0, 0, 0x04, 0x18]);
+
+    assert_eq!(typed_chain_id(&resource_id), 0x0600_0000_0418);
+}
+
+#[test]
+fn compiled_poseidon_params_match_setup_params() {
+    use arkworks_setups::common::setup_params;
+    use arkworks_setups::Curve;
+
+    let params = poseidon_params_bn254();
+    let expected = setup_params::<ark_bn254::Fr>(Curve::Bn254, 5, 3);
+
+    assert_eq!(params.round_keys, expected.round_keys);
+    assert_eq!(params.mds_matrix, expected.mds_matrix);
+    assert_eq!(params.full_rounds, expected.full_rounds);
+    assert_eq!(params.partial_rounds, expected.partial_rounds);
+    assert_eq!(params.width, expected.width);
+    assert_eq!(params.sbox.0, expected.sbox.0);
+
+    // every level of the empty tree hashes into the next one
+    let hasher = PoseidonHasherBn254::new();
+    for level in 0..4 {
+        assert_eq!(
+            hasher.hash_left_right(zeroes(level), zeroes(level)),
+            Ok(zeroes(level + 1))
+        );
+    }
+}
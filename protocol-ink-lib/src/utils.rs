@@ -16,49 +16,13 @@ pub fn element_encoder(v: &[u8]) -> [u8; 32] {
     output
 }
 
-/// Transforms a u8 array to a fixed size array of 4 bytes
+/// Parses the typed chain id from the last 6 bytes of a resource id
 ///
-/// * `v` -  u8 array to transform
-pub fn element_encoder_for_four_bytes(v: &[u8]) -> [u8; 4] {
-    let mut output = [0u8; 4];
-    output.iter_mut().zip(v).for_each(|(b1, b2)| *b1 = *b2);
-    output
-}
-
-/// Transforms a u8 array to a fixed size array of 8 bytes
-///
-/// * `v` -  u8 array to transform
-pub fn element_encoder_for_eight_bytes(v: &[u8]) -> [u8; 8] {
-    let mut output = [0u8; 8];
-    output.iter_mut().zip(v).for_each(|(b1, b2)| *b1 = *b2);
-    output
-}
-
-/// Transforms a u8 array to a fixed size array of 16 bytes
-///
-/// * `v` -  u8 array to transform
-pub fn element_encoder_for_sixteen_bytes(v: &[u8]) -> [u8; 16] {
-    let mut output = [0u8; 16];
-    output.iter_mut().zip(v).for_each(|(b1, b2)| *b1 = *b2);
-    output
-}
-
-/// Transforms a u8 array to a fixed size array of 1 byte
-///
-/// * `v` -  u8 array to transform
-pub fn element_encoder_for_one_byte(v: &[u8]) -> [u8; 1] {
-    let mut output = [0u8; 1];
-    output.iter_mut().zip(v).for_each(|(b1, b2)| *b1 = *b2);
-    output
-}
-
-/// Transforms a u8 array to a fixed size array of 2 bytes
-///
-/// * `v` -  u8 array to transform
-pub fn element_encoder_for_two_bytes(v: &[u8]) -> [u8; 2] {
-    let mut output = [0u8; 2];
-    output.iter_mut().zip(v).for_each(|(b1, b2)| *b1 = *b2);
-    output
+/// * `resource_id` - the resource id of a proposal
+pub fn typed_chain_id(resource_id: &[u8; 32]) -> u64 {
+    let mut typed_chain_id_bytes = [0u8; 8];
+    typed_chain_id_bytes[2..].copy_from_slice(&resource_id[26..32]);
+    u64::from_be_bytes(typed_chain_id_bytes)
 }
 
 /// Determines if an account is zero token address
@@ -0,0 +1,32 @@
+//! The chain extension through which the mixer and VAnchor verifiers verify proofs natively
+use ink_lang as ink;
+use ink_prelude::vec::Vec;
+
+#[ink::chain_extension]
+pub trait VerifyProof {
+    type ErrorCode = VerifyProofErr;
+
+    /// Verifies a proof natively through the runtime verifier the mixer uses, the input is
+    /// (public_inp_bytes, proof_bytes) and the proof is checked against the one verifying key
+    /// configured in the runtime, so it is only used for the circuit of that key
+    #[ink(extension = 1101, returns_result = false)]
+    fn verify_proof(input: (Vec<u8>, Vec<u8>)) -> bool;
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VerifyProofErr {
+    /// Returned if the runtime failed at verifying the proof
+    FailGetVerifyProof,
+    /// Returned if the runtime answered with a status code it doesn't document
+    UnknownStatusCode,
+}
+
+impl ink_env::chain_extension::FromStatusCode for VerifyProofErr {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            1 => Err(Self::FailGetVerifyProof),
+            _ => Err(Self::UnknownStatusCode),
+        }
+    }
+}
@@ -10,13 +10,14 @@ import {
   toEncodedBinary,
   genResourceId,
 } from "./util";
-import { hexToU8a } from "@polkadot/util";
+import { hexToU8a, u8aToHex } from "@polkadot/util";
+import { blake2AsU8a } from "@polkadot/util-crypto";
 import { createType } from "@polkadot/types";
 import keccak256 from "keccak256";
 import { BigNumber, BigNumberish } from "ethers";
 import { registry } from "@subsocial/types";
 
-const { getContractFactory, getRandomSigner } = patract;
+const { getContractFactory, getContractAt, getRandomSigner } = patract;
 const { api, getAddresses, getSigners } = network;
 
 export function normalizeBalance(balance: number): BN {
@@ -87,6 +88,8 @@ describe("anchor-handler-tests", () => {
       maxEdges,
       chainId,
       levels,
+      rootHistorySize,
+      neighborRootHistorySize,
       maxDepositAmount,
       minWithdrwalAmount,
       maxExtAmt,
@@ -152,6 +155,17 @@ describe("anchor-handler-tests", () => {
     );
     const poseidonContract = await poseidonContractFactory.deploy("new");
 
+    // VAnchor verifier instantiation
+    const vAnchorVerifierContractFactory = await getContractFactory(
+      "vanchor_verifier",
+      sender.address
+    );
+    const vAnchorVerifierContract = await vAnchorVerifierContractFactory.deploy(
+      "new",
+      maxEdges,
+      sender.address
+    );
+
     const randomVersion = Math.floor(Math.random() * 10000);
 
     const vAnchorContractFactory = await getContractFactory(
@@ -164,11 +178,13 @@ describe("anchor-handler-tests", () => {
       maxEdges,
       chainId,
       levels,
+      rootHistorySize,
+      neighborRootHistorySize,
       maxDepositAmount,
       minWithdrwalAmount,
       maxExtAmt,
       maxFee,
-      tokenWrapperContract.address,
+      sender.address,
       sender.address,
       {
         tokenName,
@@ -184,6 +200,7 @@ describe("anchor-handler-tests", () => {
       },
       randomVersion + 1,
       poseidonContract.abi.info.source.wasmHash,
+      vAnchorVerifierContract.abi.info.source.wasmHash,
       tokenWrapperContract.abi.info.source.wasmHash
     );
 
@@ -197,10 +214,10 @@ describe("anchor-handler-tests", () => {
       hexToU8a("0x00000000000000000000000000000000")
     );
 
-    const tokenWrapperContractAddress = tokenWrapperContract.address;
     const senderAddress = sender.address;
     const poseidonContractHash = poseidonContract.abi.info.source.wasmHash;
-    //const verifierContractHash =   vAnchorVerifierContract.abi.info.source.wasmHash;
+    const verifierContractHash =
+      vAnchorVerifierContract.abi.info.source.wasmHash;
     const tokenWrapperContractHash =
       tokenWrapperContract.abi.info.source.wasmHash;
 
@@ -218,14 +235,16 @@ describe("anchor-handler-tests", () => {
         maxEdges,
         chainId,
         levels,
+        rootHistorySize,
+        neighborRootHistorySize,
         maxDepositAmount,
         minWithdrwalAmount,
         maxExtAmt,
         maxFee,
-        tokenWrapperContractAddress,
-        handler: sender.address,
+        verifierGovernor: sender.address,
         randomVersion,
         poseidonContractHash,
+        verifierContractHash,
         tokenWrapperContractHash,
       },
       {
@@ -267,6 +286,8 @@ describe("anchor-handler-tests", () => {
     let maxEdges = 2;
     let chainId = 1;
     let levels = 30;
+    let rootHistorySize = 100;
+    let neighborRootHistorySize = 30;
     let maxDepositAmount = 1000000;
     let minWithdrwalAmount = 100;
     let maxExtAmt = 100;
@@ -276,6 +297,8 @@ describe("anchor-handler-tests", () => {
       maxEdges,
       chainId,
       levels,
+      rootHistorySize,
+      neighborRootHistorySize,
       maxDepositAmount,
       minWithdrwalAmount,
       maxExtAmt,
@@ -388,7 +411,7 @@ describe("anchor-handler-tests", () => {
 
     let parsedFunctionSig = JSON.parse(functionSig.output).ok;
 
-    let nonce = [0, 0, 0, 0, 0, 0, 0, 0];
+    let nonce = [0, 0, 0, 1];
 
     let dataResult =
       await anchorHandlerContract.query.constructDataForSetHandler(
@@ -430,6 +453,7 @@ describe("anchor-handler-tests", () => {
       await anchorHandlerContract.query.constructDataForUpdateEdge(
         resourceId,
         parsedFunctionSig,
+        [0, 0, 0, 1],
         [0, 0, 0, 0, 0, 0, 0, 1],
         [
           0, 0, 0, 0, 0, 0, 4, 24, 0, 0, 0, 0, 0, 0, 4, 24, 0, 0, 0, 0, 0, 0, 4,
@@ -442,12 +466,47 @@ describe("anchor-handler-tests", () => {
         ]
       );
 
+    // the handler instantiated the VAnchor with itself as the handler
+    let vAnchorAddressResult =
+      await anchorHandlerContract.query.getVanchorAddress();
+    let handledVAnchorContract = await getContractAt(
+      "vanchor",
+      JSON.parse(vAnchorAddressResult.output).ok,
+      sender
+    );
+    let handlerResult = await handledVAnchorContract.query.handler();
+    expect(handlerResult.output.toString()).to.equal(
+      anchorHandlerContract.address.toString()
+    );
+
+    let executeResult = await anchorHandlerContract.query.executeProposal(
+      resourceId,
+      JSON.parse(dataResult.output).ok
+    );
+    expect(JSON.parse(executeResult.output).err).to.be.undefined;
+
     await expect(
       anchorHandlerContract.tx.executeProposal(
         resourceId,
         JSON.parse(dataResult.output).ok
       )
     ).to.be.fulfilled;
+
+    // the edge is added on the VAnchor
+    let edgeResult = await handledVAnchorContract.query.getEdge(1);
+    expect(edgeResult.output.isSome).to.be.true;
+
+    // the update is recorded under the source chain of the edge
+    let countResult = await anchorHandlerContract.query.getUpdateRecordCount(1);
+    expect(Number(countResult.output)).to.equal(1);
+    let recordResult =
+      await anchorHandlerContract.query.readLatestUpdateRecord(1);
+    let record = JSON.parse(recordResult.output).ok;
+    expect(record.functionSignature).to.equal(parsedFunctionSig);
+    expect(record.proposalHash).to.equal(
+      u8aToHex(blake2AsU8a(hexToU8a(JSON.parse(dataResult.output).ok), 256))
+    );
+    expect(record.leafId).to.equal(0);
   });
 
   it("Execute Proposal for configure max deposit limit", async () => {
@@ -476,6 +535,7 @@ describe("anchor-handler-tests", () => {
       await anchorHandlerContract.query.constructDataForLimitAmount(
         resourceId,
         parsedFunctionSig,
+        [0, 0, 0, 1],
         amount
       );
 
@@ -513,6 +573,7 @@ describe("anchor-handler-tests", () => {
       await anchorHandlerContract.query.constructDataForLimitAmount(
         resourceId,
         parsedFunctionSig,
+        [0, 0, 0, 2],
         amount
       );
 
@@ -523,4 +584,73 @@ describe("anchor-handler-tests", () => {
       )
     ).to.be.fulfilled;
   });
+
+  it("Set and unset resource are restricted to the bridge and the admin", async () => {
+    let resourceId = Array.from(genResourceId(psp22Contract.address));
+    const eveHandlerContract = anchorHandlerContract.connect(EveSigner);
+
+    // a caller that is neither the bridge nor the admin is rejected
+    let setResult = await eveHandlerContract.query.setResource(
+      resourceId,
+      psp22Contract.address
+    );
+    expect(JSON.parse(setResult.output).err).to.equal("Unauthorized");
+
+    await expect(
+      anchorHandlerContract.tx.setResource(resourceId, psp22Contract.address)
+    ).to.emit(anchorHandlerContract, "ResourceAdded");
+
+    let unsetResult = await eveHandlerContract.query.unsetResource(resourceId);
+    expect(JSON.parse(unsetResult.output).err).to.equal("Unauthorized");
+
+    await expect(anchorHandlerContract.tx.unsetResource(resourceId)).to.emit(
+      anchorHandlerContract,
+      "ResourceRemoved"
+    );
+
+    // validate that contract address is no longer whitelisted
+    let isContractWhitelistedResult =
+      await anchorHandlerContract.query.isContractAddressWhitelisted(
+        psp22Contract.address
+      );
+    expect(JSON.parse(isContractWhitelistedResult.output).ok).to.be.false;
+  });
+
+  it("Renouncing the admin leaves only the bridge to set resources", async () => {
+    let resourceId = Array.from(genResourceId(psp22Contract.address));
+
+    // the deployer is the bridge and the admin, hand the bridge over to Bob
+    await expect(anchorHandlerContract.tx.migrateBridge(BobSigner.address)).to
+      .be.fulfilled;
+
+    // only the admin can renounce
+    const eveHandlerContract = anchorHandlerContract.connect(EveSigner);
+    let renounceResult = await eveHandlerContract.query.renounceAdmin();
+    expect(JSON.parse(renounceResult.output).err).to.equal("Unauthorized");
+
+    await expect(anchorHandlerContract.tx.renounceAdmin()).to.be.fulfilled;
+    let adminResult = await anchorHandlerContract.query.getAdmin();
+    expect(JSON.parse(adminResult.output).ok).to.not.equal(sender.address);
+
+    // the former admin can't set nor unset resources anymore
+    let setResult = await anchorHandlerContract.query.setResource(
+      resourceId,
+      psp22Contract.address
+    );
+    expect(JSON.parse(setResult.output).err).to.equal("Unauthorized");
+    let unsetResult = await anchorHandlerContract.query.unsetResource(
+      resourceId
+    );
+    expect(JSON.parse(unsetResult.output).err).to.equal("Unauthorized");
+
+    // the bridge still can
+    const bridgeHandlerContract = anchorHandlerContract.connect(BobSigner);
+    await expect(
+      bridgeHandlerContract.tx.setResource(resourceId, psp22Contract.address)
+    ).to.emit(anchorHandlerContract, "ResourceAdded");
+    await expect(bridgeHandlerContract.tx.unsetResource(resourceId)).to.emit(
+      anchorHandlerContract,
+      "ResourceRemoved"
+    );
+  });
 });
@@ -107,6 +107,7 @@ describe("mixer", () => {
     // Mixer instantiation
     const randomVersion = Math.floor(Math.random() * 10000);
     const levels = 30;
+    const rootHistorySize = 100;
     const depositSize = 100000000;
     const mixerContractFactory = await getContractFactory(
       "mixer",
@@ -115,6 +116,7 @@ describe("mixer", () => {
     const mixerContract = await mixerContractFactory.deploy(
       "new",
       levels,
+      rootHistorySize,
       depositSize,
       randomVersion,
       poseidonContract.abi.info.source.wasmHash,
@@ -3,7 +3,7 @@ import { network, patract } from "redspot";
 import BN from "bn.js";
 import { killContractNode, startContractNode, genResourceId } from "./util";
 import { Keyring } from "@polkadot/keyring";
-import { mnemonicGenerate } from "@polkadot/util-crypto";
+import { blake2AsU8a, mnemonicGenerate } from "@polkadot/util-crypto";
 
 const { getContractFactory, getRandomSigner } = patract;
 const { api, getAddresses, getSigners } = network;
@@ -351,4 +351,86 @@ describe("signature-bridge-tests", () => {
       signatureBridgeContract.tx.executeProposalWithSignature(data, sig)
     ).to.be.fulfilled;
   });
+
+  it("signature bridge rejects replayed and unsigned proposals", async () => {
+    let resourceId = Array.from(genResourceId(psp22Contract.address));
+
+    let functionSig =
+      await tokenWrapperHandlerContract.query.getSetFeeFunctionSignature();
+
+    let parsedFunctionSig = JSON.parse(functionSig.output).ok;
+
+    let nonce = [0, 0, 4, 24];
+
+    let dataResult = await signatureBridgeContract.query.constructData(
+      resourceId,
+      functionSig,
+      nonce,
+      resourceId,
+      tokenWrapperHandlerContract.address,
+      tokenWrapperContract.address
+    );
+
+    let data = JSON.parse(dataResult.output).ok;
+
+    const sig = Array.from(pair.sign(data));
+
+    await expect(
+      signatureBridgeContract.tx.adminSetResourceWithSignature({
+        resourceId,
+        functionSig,
+        nonce,
+        newResourceId: resourceId,
+        handlerAddress: tokenWrapperHandlerContract.address,
+        executionContextAddress: tokenWrapperContract.address,
+        sig,
+      })
+    ).to.be.fulfilled;
+
+    let setFeeDataResult =
+      await tokenWrapperHandlerContract.query.constructDataForSetFee(
+        resourceId,
+        parsedFunctionSig,
+        1,
+        100
+      );
+
+    let setFeeData = JSON.parse(setFeeDataResult.output).ok;
+
+    // a proposal not signed by the governor is rejected
+    const otherPair = keyring.addFromUri(mnemonicGenerate());
+    let unsignedResult =
+      await signatureBridgeContract.query.executeProposalWithSignature(
+        setFeeData,
+        Array.from(otherPair.sign(setFeeData))
+      );
+    expect(JSON.parse(unsignedResult.output).err).to.equal(
+      "InvalidSignatureFromGovernor"
+    );
+
+    const setFeeSig = Array.from(pair.sign(setFeeData));
+    const proposalHash = Array.from(blake2AsU8a(setFeeData, 256));
+
+    await expect(
+      signatureBridgeContract.tx.executeProposalWithSignature(
+        setFeeData,
+        setFeeSig
+      )
+    ).to.emit(signatureBridgeContract, "ProposalExecuted");
+
+    let isExecuted = await signatureBridgeContract.query.isProposalExecuted(
+      proposalHash
+    );
+    expect(isExecuted.output.toJSON()).to.be.true;
+
+    // the same signed proposal can't be executed twice
+    let replayResult =
+      await signatureBridgeContract.query.executeProposalWithSignature(
+        setFeeData,
+        setFeeSig
+      );
+    expect(JSON.parse(replayResult.output).err).to.equal(
+      "ProposalAlreadyExecuted"
+    );
+  });
 });
@@ -463,4 +463,78 @@ describe("token-wrapper-handler-tests", () => {
       )
     ).to.be.fulfilled;
   });
+
+  it("Set and unset resource are restricted to the bridge and the admin", async () => {
+    let resourceId = Array.from(genResourceId(psp22Contract.address));
+    const eveHandlerContract = tokenWrapperHandlerContract.connect(EveSigner);
+
+    // a caller that is neither the bridge nor the admin is rejected
+    let setResult = await eveHandlerContract.query.setResource(
+      resourceId,
+      psp22Contract.address
+    );
+    expect(JSON.parse(setResult.output).err).to.equal("Unauthorized");
+
+    await expect(
+      tokenWrapperHandlerContract.tx.setResource(
+        resourceId,
+        psp22Contract.address
+      )
+    ).to.emit(tokenWrapperHandlerContract, "ResourceAdded");
+
+    let unsetResult = await eveHandlerContract.query.unsetResource(resourceId);
+    expect(JSON.parse(unsetResult.output).err).to.equal("Unauthorized");
+
+    await expect(
+      tokenWrapperHandlerContract.tx.unsetResource(resourceId)
+    ).to.emit(tokenWrapperHandlerContract, "ResourceRemoved");
+
+    // validate that contract address is no longer whitelisted
+    let isContractWhitelistedResult =
+      await tokenWrapperHandlerContract.query.isContractAddressWhitelisted(
+        psp22Contract.address
+      );
+    expect(JSON.parse(isContractWhitelistedResult.output).ok).to.be.false;
+  });
+
+  it("Renouncing the admin leaves only the bridge to set resources", async () => {
+    let resourceId = Array.from(genResourceId(psp22Contract.address));
+
+    // the deployer is the bridge and the admin, hand the bridge over to Bob
+    await expect(
+      tokenWrapperHandlerContract.tx.migrateBridge(BobSigner.address)
+    ).to.be.fulfilled;
+
+    // only the admin can renounce
+    const eveHandlerContract = tokenWrapperHandlerContract.connect(EveSigner);
+    let renounceResult = await eveHandlerContract.query.renounceAdmin();
+    expect(JSON.parse(renounceResult.output).err).to.equal("Unauthorized");
+
+    await expect(tokenWrapperHandlerContract.tx.renounceAdmin()).to.be
+      .fulfilled;
+    let adminResult = await tokenWrapperHandlerContract.query.getAdmin();
+    expect(JSON.parse(adminResult.output).ok).to.not.equal(sender.address);
+
+    // the former admin can't set nor unset resources anymore
+    let setResult = await tokenWrapperHandlerContract.query.setResource(
+      resourceId,
+      psp22Contract.address
+    );
+    expect(JSON.parse(setResult.output).err).to.equal("Unauthorized");
+    let unsetResult = await tokenWrapperHandlerContract.query.unsetResource(
+      resourceId
+    );
+    expect(JSON.parse(unsetResult.output).err).to.equal("Unauthorized");
+
+    // the bridge still can
+    const bridgeHandlerContract =
+      tokenWrapperHandlerContract.connect(BobSigner);
+    await expect(
+      bridgeHandlerContract.tx.setResource(resourceId, psp22Contract.address)
+    ).to.emit(tokenWrapperHandlerContract, "ResourceAdded");
+    await expect(bridgeHandlerContract.tx.unsetResource(resourceId)).to.emit(
+      tokenWrapperHandlerContract,
+      "ResourceRemoved"
+    );
+  });
 });
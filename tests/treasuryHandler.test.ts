@@ -9,8 +9,10 @@ import {
   hexStringToByteArray,
   toEncodedBinary,
   genResourceId,
+  getChainIdType,
 } from "./util";
-import { hexToU8a } from "@polkadot/util";
+import { hexToU8a, u8aToHex } from "@polkadot/util";
+import { blake2AsU8a } from "@polkadot/util-crypto";
 import { createType } from "@polkadot/types";
 import keccak256 from "keccak256";
 import { BigNumber, BigNumberish } from "ethers";
@@ -236,6 +238,18 @@ describe("treasury-handler-tests", () => {
         JSON.parse(dataResult.output).ok
       )
     ).to.be.fulfilled;
+
+    // the proposal is recorded under the chain of the resource id
+    let recordResult =
+      await treasuryHandlerContract.query.readLatestUpdateRecord(
+        getChainIdType()
+      );
+    let record = JSON.parse(recordResult.output).ok;
+    expect(record.nonce).to.equal(1048);
+    expect(record.functionSignature).to.equal(u8aToHex(functionSig));
+    expect(record.proposalHash).to.equal(
+      u8aToHex(blake2AsU8a(hexToU8a(JSON.parse(dataResult.output).ok), 256))
+    );
   });
 
   it("Execute Proposal for rescuing tokens", async () => {
@@ -280,4 +294,73 @@ describe("treasury-handler-tests", () => {
       )
     ).to.be.fulfilled;
   });
+
+  it("Set and unset resource are restricted to the bridge and the admin", async () => {
+    let resourceId = Array.from(genResourceId(psp22Contract.address));
+    const eveHandlerContract = treasuryHandlerContract.connect(EveSigner);
+
+    // a caller that is neither the bridge nor the admin is rejected
+    let setResult = await eveHandlerContract.query.setResource(
+      resourceId,
+      psp22Contract.address
+    );
+    expect(JSON.parse(setResult.output).err).to.equal("Unauthorized");
+
+    await expect(
+      treasuryHandlerContract.tx.setResource(resourceId, psp22Contract.address)
+    ).to.emit(treasuryHandlerContract, "ResourceAdded");
+
+    let unsetResult = await eveHandlerContract.query.unsetResource(resourceId);
+    expect(JSON.parse(unsetResult.output).err).to.equal("Unauthorized");
+
+    await expect(treasuryHandlerContract.tx.unsetResource(resourceId)).to.emit(
+      treasuryHandlerContract,
+      "ResourceRemoved"
+    );
+
+    // validate that contract address is no longer whitelisted
+    let isContractWhitelistedResult =
+      await treasuryHandlerContract.query.isContractAddressWhitelisted(
+        psp22Contract.address
+      );
+    expect(JSON.parse(isContractWhitelistedResult.output).ok).to.be.false;
+  });
+
+  it("Renouncing the admin leaves only the bridge to set resources", async () => {
+    let resourceId = Array.from(genResourceId(psp22Contract.address));
+
+    // the deployer is the bridge and the admin, hand the bridge over to Bob
+    await expect(treasuryHandlerContract.tx.migrateBridge(BobSigner.address)).to
+      .be.fulfilled;
+
+    // only the admin can renounce
+    const eveHandlerContract = treasuryHandlerContract.connect(EveSigner);
+    let renounceResult = await eveHandlerContract.query.renounceAdmin();
+    expect(JSON.parse(renounceResult.output).err).to.equal("Unauthorized");
+
+    await expect(treasuryHandlerContract.tx.renounceAdmin()).to.be.fulfilled;
+    let adminResult = await treasuryHandlerContract.query.getAdmin();
+    expect(JSON.parse(adminResult.output).ok).to.not.equal(sender.address);
+
+    // the former admin can't set nor unset resources anymore
+    let setResult = await treasuryHandlerContract.query.setResource(
+      resourceId,
+      psp22Contract.address
+    );
+    expect(JSON.parse(setResult.output).err).to.equal("Unauthorized");
+    let unsetResult = await treasuryHandlerContract.query.unsetResource(
+      resourceId
+    );
+    expect(JSON.parse(unsetResult.output).err).to.equal("Unauthorized");
+
+    // the bridge still can
+    const bridgeHandlerContract = treasuryHandlerContract.connect(BobSigner);
+    await expect(
+      bridgeHandlerContract.tx.setResource(resourceId, psp22Contract.address)
+    ).to.emit(treasuryHandlerContract, "ResourceAdded");
+    await expect(bridgeHandlerContract.tx.unsetResource(resourceId)).to.emit(
+      treasuryHandlerContract,
+      "ResourceRemoved"
+    );
+  });
 });
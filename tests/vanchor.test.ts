@@ -0,0 +1,349 @@
+import { expect } from "chai";
+import { network, patract } from "redspot";
+import BN from "bn.js";
+import { killContractNode, startContractNode } from "./util";
+import { hexToU8a } from "@polkadot/util";
+import { Keyring } from "@polkadot/keyring";
+
+const { getContractFactory, getContractAt, getRandomSigner } = patract;
+const { api, getSigners } = network;
+
+// Groth16 verifying key of a circuit binding the nine public inputs of a 2-2 transaction
+// with two edges, the proofs below are generated against it
+const VERIFYING_KEY =
+  "0x6d7e5a330ed4a0b17fca702b865c59fae6fdc35b2a8c08f1d110eab8656607843854f0bb0a7d8bf501494b0bdfb1cee99f77f545b1a13fb3722a10ff18ea6217ed30963a635ff270e367437dfa084030d19e4803b49396230b934c8503652a2a3efb2ee65261a393f81e0213d5228437172131daf2b70ee328156091c51f1b2268951244cd491cdc91d6f514f7ae7a4470f6cefb4dcab0cd3413d3fb102baa2920d0ec0071468685f77f80ef8f3954d3ad8546636823e55d452c1c3aeb711607b85e68b80d52fcad0a09938ca1105401bf803461a299b9ca2b4b0f558baa121f0a000000000000002746fe84f1571c96df8c145383fc074f96f76baba8361567ffb4636b496e380c70ab77e3650188f6cb1d7a329cb7f008946a0d1b0e6d0761f4d38eccb8269b0db10f872153506a9937f8caa8c0115b5da386deca884537a4457aa15a7813162336144df45f53c8fe3494e8ce7e7fcad26b432f868dd098dd3934b14d56ab8113fc4d37aa7c5a172efd4922e716a558526e7f48d27b98d35360017e0f1d8ff21fd1d20d4c04ba1b6e947ecdbff9f505880b69222aef5298130fa8d84184c6c696198ce91a70306331228f76363eaff3ce19f7ea43e8144dbf84f36b84e7ed5a249ac33f87d9e2fb86fe1c56d647f40d271ec4e57cf09523b616d64c1a71232a9c307a514c37702b1946da17bd29b269fe6cefbea5fffde1642c02c71b01747717fbdbcd9f1de05d260be8a60dd06384925fc57c669efb42148575013147dbad01";
+
+// the root of an empty tree of 30 levels
+const EMPTY_ROOT =
+  "0xa08ada5f56b41e0b15574c94dbac09a99d79168791bdf8bc78e347895f58151f";
+
+// withdraws 100 to Bob paying a fee of 5 to Charlie
+const WITHDRAWAL = {
+  proof:
+    "0xfced7e57558743f066f6d78929a8afb3d0ac789f48f1aa52c56781e26c68dca93348992355e1122a8843dba096bbca395322ec7339feb1e118265980a532f82163691db9899713a9a5fc3b8ee12743c525d29f7c6c97c4e196e03047d4ed6996d84dfbdd80f315779ecb7be06434d80919347f1121d93858022431f610bc8b1a",
+  publicAmount:
+    "0x98ffffef93f5e1439170b97948e833285d588181b64550b829a031e1724e6430",
+  extDataHash:
+    "0xd0eac0f6c3f06acdfc44e6213b80f02d1deec2d7b35388f911c09e7d8020461f",
+  inputNullifiers: [
+    "0x0101000000000000000000000000000000000000000000000000000000000000",
+    "0x0102000000000000000000000000000000000000000000000000000000000000",
+  ],
+  outputCommitments: [
+    "0x0103000000000000000000000000000000000000000000000000000000000000",
+    "0x0104000000000000000000000000000000000000000000000000000000000000",
+  ],
+  extAmount: -100,
+  fee: 5,
+  encryptedOutput1: "0x01010101",
+  encryptedOutput2: "0x0b0b0b0b",
+};
+
+// deposits 100 for Bob without a fee
+const DEPOSIT = {
+  proof:
+    "0x200be68af9ef914fc4730a576adf243e67231e3e2af2a986ea2bf9c56d3e7c0a60213fb81e8b9bd973976cdb771a2f2ef1a3ff77b78c643563b854bf3cfa9d26613a36f9f6ae17075ce23cb4a48396a8e30acde95bebe068671c52f95d353a20c7a1bd6fd3ca19d86acd42fa292dfe5212edb68125b7037b940197aee910d192",
+  publicAmount:
+    "0x6400000000000000000000000000000000000000000000000000000000000000",
+  extDataHash:
+    "0x5aed2da2e012d4a18976c14d27f4a9fffbae3c3746ad789101f19ec23f6ecb0f",
+  inputNullifiers: [
+    "0x0201000000000000000000000000000000000000000000000000000000000000",
+    "0x0202000000000000000000000000000000000000000000000000000000000000",
+  ],
+  outputCommitments: [
+    "0x0203000000000000000000000000000000000000000000000000000000000000",
+    "0x0204000000000000000000000000000000000000000000000000000000000000",
+  ],
+  extAmount: 100,
+  fee: 0,
+  encryptedOutput1: "0x02020202",
+  encryptedOutput2: "0x0c0c0c0c",
+};
+
+function toBytes(hex: string): number[] {
+  return Array.from(hexToU8a(hex));
+}
+
+describe("vanchor-tests", () => {
+  let vAnchorContract: any;
+  let anchorTokenWrapperContract: any;
+  let tokenWrapperContract: any;
+  let sender: any;
+  let Bob: string;
+  let Charlie: string;
+  let childProcess: any;
+  after(() => {
+    killContractNode(childProcess);
+    return api.disconnect();
+  });
+
+  before(async () => {
+    childProcess = await startContractNode();
+    await api.isReady;
+  });
+
+  beforeEach(async () => {
+    ({
+      vAnchorContract,
+      anchorTokenWrapperContract,
+      tokenWrapperContract,
+      sender,
+      Bob,
+      Charlie,
+    } = await setup());
+  });
+
+  async function setup() {
+    const one = new BN(10).pow(new BN(api.registry.chainDecimals[0]));
+    const signers = await getSigners();
+    const sender = await getRandomSigner(signers[0], one.muln(10));
+
+    // the proofs commit to the dev accounts as recipient and relayer
+    const keyring = new Keyring({ type: "sr25519" });
+    const Bob = keyring.addFromUri("//Bob").address;
+    const Charlie = keyring.addFromUri("//Charlie").address;
+
+    const tokenWrapperData = {
+      name: "Webb",
+      symbol: "Webb",
+      decimal: 4,
+      governor: sender.address,
+      feeRecipient: sender.address,
+      feePercentage: 1,
+      isNativeAllowed: true,
+      wrappingLimit: 1_000_000_000_01,
+      proposalNonce: 0,
+      totalSupply: 1_000_000_000,
+    };
+
+    const tokenWrapperContractFactory = await getContractFactory(
+      "governed_token_wrapper",
+      sender.address
+    );
+    const tokenWrapperContract = await tokenWrapperContractFactory.deploy(
+      "new",
+      tokenWrapperData.name,
+      tokenWrapperData.symbol,
+      tokenWrapperData.decimal,
+      tokenWrapperData.governor,
+      tokenWrapperData.feeRecipient,
+      tokenWrapperData.feePercentage,
+      tokenWrapperData.isNativeAllowed,
+      tokenWrapperData.wrappingLimit,
+      tokenWrapperData.proposalNonce,
+      tokenWrapperData.totalSupply
+    );
+
+    const vAnchorVerifierContractFactory = await getContractFactory(
+      "vanchor_verifier",
+      sender.address
+    );
+    const vAnchorVerifierContract = await vAnchorVerifierContractFactory.deploy(
+      "new",
+      2,
+      sender.address
+    );
+
+    const vAnchorContractFactory = await getContractFactory(
+      "vanchor",
+      sender.address
+    );
+    const vAnchorContract = await vAnchorContractFactory.deploy(
+      "new",
+      2,
+      1,
+      30,
+      100,
+      30,
+      1_000_000,
+      10,
+      1_000_000,
+      100,
+      sender.address,
+      sender.address,
+      tokenWrapperData,
+      Math.floor(Math.random() * 10000),
+      null,
+      vAnchorVerifierContract.abi.info.source.wasmHash,
+      tokenWrapperContract.abi.info.source.wasmHash
+    );
+
+    // register the verifying key the test proofs are generated against
+    const verifierAddress = (await vAnchorContract.query.verifier()).output;
+    const verifierContract = await getContractAt(
+      "vanchor_verifier",
+      verifierAddress.toString(),
+      sender
+    );
+    await expect(verifierContract.tx.setVerificationBackend("Wasm")).to.be
+      .fulfilled;
+    await expect(
+      verifierContract.tx.registerVerifyingKey(2, 2, 2, toBytes(VERIFYING_KEY))
+    ).to.be.fulfilled;
+
+    const tokenWrapperAddress = (await vAnchorContract.query.tokenWrapper())
+      .output;
+    const anchorTokenWrapperContract = await getContractAt(
+      "governed_token_wrapper",
+      tokenWrapperAddress.toString(),
+      sender
+    );
+
+    return {
+      vAnchorContract,
+      anchorTokenWrapperContract,
+      tokenWrapperContract,
+      sender,
+      Bob,
+      Charlie,
+    };
+  }
+
+  function proofData(transaction: any) {
+    return {
+      proof: toBytes(transaction.proof),
+      publicAmount: toBytes(transaction.publicAmount),
+      roots: [toBytes(EMPTY_ROOT), toBytes(EMPTY_ROOT)],
+      inputNullifiers: transaction.inputNullifiers.map(toBytes),
+      outputCommitments: transaction.outputCommitments.map(toBytes),
+      extDataHash: toBytes(transaction.extDataHash),
+    };
+  }
+
+  function extData(transaction: any) {
+    return {
+      recipient: Bob,
+      relayer: Charlie,
+      extAmount: transaction.extAmount,
+      fee: transaction.fee,
+      encryptedOutput1: toBytes(transaction.encryptedOutput1),
+      encryptedOutput2: toBytes(transaction.encryptedOutput2),
+    };
+  }
+
+  async function wrappedBalance(address: string): Promise<number> {
+    const balance = await anchorTokenWrapperContract.query.psp22Balance(
+      address
+    );
+    return Number(balance.output);
+  }
+
+  it("Withdraw pays the recipient and the relayer from the pool", async () => {
+    // the pool holds the wrapped tokens of earlier deposits
+    await expect(
+      anchorTokenWrapperContract.tx.insertPsp22Balance(
+        vAnchorContract.address,
+        1000
+      )
+    ).to.be.fulfilled;
+
+    const withdrawResult = await vAnchorContract.query.transactWithdraw(
+      proofData(WITHDRAWAL),
+      extData(WITHDRAWAL)
+    );
+    expect(JSON.parse(withdrawResult.output).err).to.be.undefined;
+
+    await expect(
+      vAnchorContract.tx.transactWithdraw(
+        proofData(WITHDRAWAL),
+        extData(WITHDRAWAL)
+      )
+    ).to.be.fulfilled;
+
+    expect(await wrappedBalance(Bob)).to.equal(100);
+    expect(await wrappedBalance(Charlie)).to.equal(5);
+    expect(await wrappedBalance(vAnchorContract.address)).to.equal(895);
+
+    const isSpent = await vAnchorContract.query.isKnownNullifier(
+      toBytes(WITHDRAWAL.inputNullifiers[0])
+    );
+    expect(isSpent.output.toJSON()).to.be.true;
+  });
+
+  it("Withdraw pays the relayer fee once, on top of the withdrawn amount", async () => {
+    await expect(
+      anchorTokenWrapperContract.tx.insertPsp22Balance(
+        vAnchorContract.address,
+        1000
+      )
+    ).to.be.fulfilled;
+
+    await expect(
+      vAnchorContract.tx.transactWithdraw(
+        proofData(WITHDRAWAL),
+        extData(WITHDRAWAL)
+      )
+    ).to.emit(vAnchorContract, "Transaction");
+
+    // the recipient gets |extAmount| and the relayer the fee, out of the pool
+    const withdrawn = -WITHDRAWAL.extAmount;
+    expect(await wrappedBalance(Bob)).to.equal(withdrawn);
+    expect(await wrappedBalance(Charlie)).to.equal(WITHDRAWAL.fee);
+    expect(await wrappedBalance(vAnchorContract.address)).to.equal(
+      1000 - withdrawn - WITHDRAWAL.fee
+    );
+
+    // replaying the withdrawal pays nobody again
+    const replayResult = await vAnchorContract.query.transactWithdraw(
+      proofData(WITHDRAWAL),
+      extData(WITHDRAWAL)
+    );
+    expect(JSON.parse(replayResult.output).err).to.equal(
+      "AlreadyRevealedNullfier"
+    );
+    expect(await wrappedBalance(Bob)).to.equal(withdrawn);
+    expect(await wrappedBalance(Charlie)).to.equal(WITHDRAWAL.fee);
+  });
+
+  it("Deposit is rejected when the caller doesn't pay the wrapped tokens", async () => {
+    // the sender holds no wrapped tokens and approved nothing
+    expect(await wrappedBalance(sender.address)).to.equal(0);
+
+    const depositResult = await vAnchorContract.query.transactDeposit(
+      proofData(DEPOSIT),
+      extData(DEPOSIT),
+      anchorTokenWrapperContract.address,
+      DEPOSIT.extAmount
+    );
+    expect(JSON.parse(depositResult.output).err).to.equal("TransferError");
+
+    const lastRoot = await vAnchorContract.query.getLastRoot();
+    expect(lastRoot.output.toHex()).to.equal(EMPTY_ROOT);
+    expect(await wrappedBalance(vAnchorContract.address)).to.equal(0);
+  });
+
+  it("Deposit moves the wrapped tokens from the caller to the pool", async () => {
+    // the sender holds wrapped tokens and lets the pool spend the deposit
+    await expect(
+      anchorTokenWrapperContract.tx.insertPsp22Balance(sender.address, 1000)
+    ).to.be.fulfilled;
+    await expect(
+      anchorTokenWrapperContract.tx.setPsp22Allowance(
+        vAnchorContract.address,
+        DEPOSIT.extAmount
+      )
+    ).to.be.fulfilled;
+
+    // a token other than the pool's wrapped token is refused
+    const otherTokenResult = await vAnchorContract.query.transactDeposit(
+      proofData(DEPOSIT),
+      extData(DEPOSIT),
+      tokenWrapperContract.address,
+      DEPOSIT.extAmount
+    );
+    expect(JSON.parse(otherTokenResult.output).err).to.equal("Unauthorized");
+
+    await expect(
+      vAnchorContract.tx.transactDeposit(
+        proofData(DEPOSIT),
+        extData(DEPOSIT),
+        anchorTokenWrapperContract.address,
+        DEPOSIT.extAmount
+      )
+    ).to.be.fulfilled;
+
+    expect(await wrappedBalance(sender.address)).to.equal(900);
+    expect(await wrappedBalance(vAnchorContract.address)).to.equal(100);
+  });
+});